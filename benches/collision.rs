@@ -0,0 +1,125 @@
+// Compares the collision checks before and after the spatial grid: the old nested loops over
+// every missile/asteroid and player/asteroid pair, against rebuilding the grid and querying it.
+// Run with `cargo bench`. Nothing is deleted, both paths only count hits so every round
+// sees the same world.
+use std::time::{Duration, Instant};
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use specs::{World, WorldExt, Join, RunNow};
+
+use asteroid_game::components;
+use asteroid_game::game;
+use asteroid_game::resources::Arena;
+use asteroid_game::spatial::{SpatialGrid, SpatialGridBuilder};
+use asteroid_game::weapons::Weapon;
+
+const ASTEROID_COUNTS: [usize; 3] = [1_000, 10_000, 50_000];
+const MISSILES: usize = 64;
+const ROUNDS: u32 = 20;
+
+fn build_world(asteroid_count: usize) -> World {
+    let arena = Arena::default();
+    let (width, height) = (arena.width as f64, arena.height as f64);
+    let mut ecs = game::create_world(arena, 1);
+    let mut rng = StdRng::seed_from_u64(1);
+
+    for _ in 0..asteroid_count {
+        let position = components::Position{x: rng.gen_range(0.0..width), y: rng.gen_range(0.0..height), rot: rng.gen_range(0.0..360.0)};
+        let size = [32, 64, 128][rng.gen_range(0..3)];
+        game::create_asteroid(&mut ecs, position, size);
+    }
+
+    for _ in 0..MISSILES {
+        let position = components::Position{x: rng.gen_range(0.0..width), y: rng.gen_range(0.0..height), rot: 0.0};
+        game::spawn_missile(&mut ecs, position, &Weapon::default());
+    }
+
+    game::spawn_player(&mut ecs, components::Position{x: width / 2.0, y: height / 2.0, rot: 0.0}, 0);
+    ecs.maintain();
+    ecs
+}
+
+// The checks as AsteroidCollider and MissileStriker did them before the grid.
+fn brute_force_hits(ecs: &World) -> usize {
+    let positions = ecs.read_storage::<components::Position>();
+    let rends = ecs.read_storage::<components::Renderable>();
+    let asteroids = ecs.read_storage::<components::Asteroid>();
+    let missiles = ecs.read_storage::<components::Missile>();
+    let players = ecs.read_storage::<components::Player>();
+    let mut hits = 0;
+
+    for (player_pos, player_rend, _) in (&positions, &rends, &players).join() {
+        for (asteroid_pos, asteroid_rend, _) in (&positions, &rends, &asteroids).join() {
+            let diff_x: f64 = (player_pos.x - asteroid_pos.x).abs();
+            let diff_y: f64 = (player_pos.y - asteroid_pos.y).abs();
+            let hyp: f64 = ((diff_x*diff_x) + (diff_y*diff_y)).sqrt();
+            if hyp < (asteroid_rend.o_w + player_rend.o_w) as f64 / 2.0 {
+                hits += 1;
+            }
+        }
+    }
+
+    for (missile_pos, _, _) in (&positions, &rends, &missiles).join() {
+        for (asteroid_pos, asteroid_rend, _) in (&positions, &rends, &asteroids).join() {
+            let diff_x: f64 = (missile_pos.x - asteroid_pos.x).abs();
+            let diff_y: f64 = (missile_pos.y - asteroid_pos.y).abs();
+            let hyp: f64 = ((diff_x*diff_x) + (diff_y*diff_y)).sqrt();
+            if hyp < asteroid_rend.o_w as f64 / 2.0 {
+                hits += 1;
+            }
+        }
+    }
+
+    hits
+}
+
+// The same checks with the grid, including the per tick rebuild.
+fn grid_hits(ecs: &World) -> usize {
+    SpatialGridBuilder.run_now(ecs);
+
+    let grid = ecs.read_resource::<SpatialGrid>();
+    let positions = ecs.read_storage::<components::Position>();
+    let rends = ecs.read_storage::<components::Renderable>();
+    let missiles = ecs.read_storage::<components::Missile>();
+    let players = ecs.read_storage::<components::Player>();
+    let mut hits = 0;
+
+    for (player_pos, player_rend, _) in (&positions, &rends, &players).join() {
+        grid.query(player_pos.x, player_pos.y, player_rend.o_w as f64 / 2.0, |_| hits += 1);
+    }
+
+    for (missile_pos, _, _) in (&positions, &rends, &missiles).join() {
+        grid.query(missile_pos.x, missile_pos.y, 0.0, |_| hits += 1);
+    }
+
+    hits
+}
+
+fn time<F: FnMut() -> usize>(mut check: F) -> (Duration, usize) {
+    let mut hits = 0;
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        hits = check();
+    }
+    (start.elapsed() / ROUNDS, hits)
+}
+
+fn main() {
+    println!("{} missiles, 1 ship, average of {} rounds", MISSILES, ROUNDS);
+    println!("{:>10} {:>14} {:>14} {:>8}", "asteroids", "brute force", "grid", "speedup");
+
+    for asteroid_count in ASTEROID_COUNTS {
+        let ecs = build_world(asteroid_count);
+
+        let (brute_force_time, brute_force_count) = time(|| brute_force_hits(&ecs));
+        let (grid_time, grid_count) = time(|| grid_hits(&ecs));
+        assert_eq!(brute_force_count, grid_count, "the grid must find the same hits as the brute force check");
+
+        println!("{:>10} {:>14?} {:>14?} {:>7.1}x",
+            asteroid_count,
+            brute_force_time,
+            grid_time,
+            brute_force_time.as_secs_f64() / grid_time.as_secs_f64());
+    }
+}
@@ -0,0 +1,209 @@
+use std::fs;
+use std::path::Path;
+
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::components::{Chilling, Explosive};
+
+// How an asteroid breaks up once it runs out of health.
+#[derive(Deserialize, Clone, Debug)]
+pub struct SplitRule {
+    // Pieces it breaks into, 0 for none
+    pub count: u32,
+    // Degrees between the outermost pieces, centered on the asteroid's heading
+    pub spread: f64,
+    // Speed of the pieces relative to the asteroid's
+    pub speed: f64,
+    // Pieces are half the asteroid's size, and aren't made if that's below this
+    pub min_size: u32,
+    // Kind of the pieces, the asteroid's own if left out
+    pub kind: Option<String>
+}
+
+// How often a kind appears when a level starts, from the given level on.
+#[derive(Deserialize, Clone, Debug)]
+pub struct SpawnWeight {
+    pub level: u32,
+    pub weight: u32
+}
+
+// A kind of asteroid. One [[asteroid]] table in data/asteroids.toml:
+//
+//     [[asteroid]]
+//     name = "explosive"
+//     texture = "img/asteroid_explosive.png"
+//     texture_width = 256
+//     texture_height = 256
+//     min_speed = 2.0
+//     max_speed = 3.0
+//     rot_speed = 1.0
+//     size_per_health = 32
+//     explosive = { radius = 150.0, damage = 2 }
+//     spawn = [{ level = 4, weight = 2 }]
+//
+//     [asteroid.split]
+//     count = 0
+//     spread = 0.0
+//     speed = 1.0
+//     min_size = 25
+#[derive(Deserialize, Clone, Debug)]
+pub struct Archetype {
+    pub name: String,
+    // Sprite and the size of its source image
+    pub texture: String,
+    pub texture_width: u32,
+    pub texture_height: u32,
+    // Pixels per tick, picked at random in this range for asteroids that start a level
+    pub min_speed: f64,
+    pub max_speed: f64,
+    // Degrees per tick the sprite spins
+    pub rot_speed: f64,
+    // Pixels of size per hit point, rounded up
+    pub size_per_health: u32,
+    pub split: SplitRule,
+    // Damages the asteroids around it when it's destroyed
+    pub explosive: Option<Explosive>,
+    // Slows down the ship when it's close
+    pub chilling: Option<Chilling>,
+    // Weight by level, the entry with the highest level up to the current one counts.
+    // Never starts a level if left out, it can still come from a split.
+    #[serde(default)]
+    pub spawn: Vec<SpawnWeight>
+}
+
+impl Archetype {
+    // Hit points of an asteroid of this kind with the given size, at least 1.
+    pub fn health(&self, size: u32) -> u32 {
+        size.div_ceil(self.size_per_health).max(1)
+    }
+
+    // Chance of this kind starting the given level, relative to the other kinds.
+    pub fn weight(&self, level: u32) -> u32 {
+        self.spawn.iter()
+            .filter(|spawn| spawn.level <= level)
+            .max_by_key(|spawn| spawn.level)
+            .map_or(0, |spawn| spawn.weight)
+    }
+}
+
+impl Default for Archetype {
+    fn default() -> Self {
+        Archetype {
+            name: String::from("rock"),
+            texture: String::from("img/asteroid.png"),
+            texture_width: 256,
+            texture_height: 256,
+            min_speed: 2.5,
+            max_speed: 2.5,
+            rot_speed: 0.5,
+            size_per_health: 32,
+            split: SplitRule {
+                count: 2,
+                spread: 180.0,
+                speed: 1.0,
+                min_size: 25,
+                kind: None
+            },
+            explosive: None,
+            chilling: None,
+            spawn: vec![SpawnWeight{level: 1, weight: 1}]
+        }
+    }
+}
+
+// Every kind of asteroid from the data file, in the order they're listed. Inserted into the
+// World as a resource, asteroids refer to their kind by name.
+#[derive(Deserialize, Clone, Debug)]
+pub struct Archetypes {
+    #[serde(rename = "asteroid")]
+    pub list: Vec<Archetype>
+}
+
+impl Default for Archetypes {
+    fn default() -> Self {
+        Archetypes {
+            list: vec![Archetype::default()]
+        }
+    }
+}
+
+impl Archetypes {
+    pub fn parse(text: &str) -> Result<Archetypes, String> {
+        let archetypes = toml::from_str::<Archetypes>(text).map_err(|e| e.to_string())?;
+        archetypes.validate()?;
+        Ok(archetypes)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.list.is_empty() {
+            return Err("there must be at least one [[asteroid]]".to_string());
+        }
+
+        for (i, archetype) in self.list.iter().enumerate() {
+            if self.list[..i].iter().any(|other| other.name == archetype.name) {
+                return Err(format!("asteroid \"{}\" is listed twice", archetype.name));
+            }
+            if archetype.size_per_health == 0 {
+                return Err(format!("size_per_health of {} must be above 0", archetype.name));
+            }
+            if archetype.split.spread < 0.0 || archetype.split.speed < 0.0 {
+                return Err(format!("split spread and speed of {} can't be negative", archetype.name));
+            }
+            if archetype.min_speed < 0.0 || archetype.max_speed < archetype.min_speed {
+                return Err(format!("speeds of {} must be positive with min_speed up to max_speed", archetype.name));
+            }
+            if archetype.explosive.as_ref().is_some_and(|explosive| explosive.radius <= 0.0) {
+                return Err(format!("explosive radius of {} must be above 0", archetype.name));
+            }
+            if archetype.chilling.as_ref().is_some_and(|chilling| !(0.0..=1.0).contains(&chilling.slowdown)) {
+                return Err(format!("chilling slowdown of {} must be from 0 to 1", archetype.name));
+            }
+            if let Some(kind) = &archetype.split.kind {
+                if !self.list.iter().any(|other| &other.name == kind) {
+                    return Err(format!("{} splits into unknown asteroid \"{}\"", archetype.name, kind));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // The first kind, used where no other one is asked for.
+    pub fn first(&self) -> &Archetype {
+        &self.list[0]
+    }
+
+    // Picks the kind of an asteroid starting the given level, by the kinds' weights. The first
+    // kind if none has a weight for the level.
+    pub fn pick<R: Rng>(&self, level: u32, rng: &mut R) -> &Archetype {
+        let total: u32 = self.list.iter().map(|archetype| archetype.weight(level)).sum();
+        if total == 0 {
+            return self.first();
+        }
+
+        let mut roll = rng.gen_range(0..total);
+        for archetype in &self.list {
+            let weight = archetype.weight(level);
+            if roll < weight {
+                return archetype;
+            }
+            roll -= weight;
+        }
+        self.first()
+    }
+
+    // The kind with the given name, or the first one if there's none.
+    pub fn get(&self, name: &str) -> &Archetype {
+        self.list.iter().find(|archetype| archetype.name == name).unwrap_or(self.first())
+    }
+
+    // A missing file is not an error, the built in rock is used instead.
+    pub fn load(path: &Path) -> Result<Archetypes, String> {
+        if !path.exists() {
+            return Ok(Archetypes::default());
+        }
+
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Archetypes::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
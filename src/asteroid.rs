@@ -1,38 +1,22 @@
-use specs::{System, WriteStorage, Join, Entities};
-
-pub struct AsteroidMover;
+use specs::{System, ReadStorage, WriteStorage, ReadExpect, Join, Entities, Entity};
+use vector2d::Vector2D;
 
+use crate::collision::{self, Shape};
 use crate::components;
+use crate::resources::Rules;
+use crate::spatial::SpatialGrid;
+
+// Spins the asteroid sprites, they are moved by movement::Mover.
+pub struct AsteroidSpinner;
 
-impl<'a> System<'a> for AsteroidMover{
+impl<'a> System<'a> for AsteroidSpinner{
     type SystemData = (
-        WriteStorage<'a, components::Position>,
         WriteStorage<'a, components::Renderable>,
-        WriteStorage<'a, components::Asteroid>
+        ReadStorage<'a, components::Asteroid>
     );
 
     fn run(&mut self, mut data: Self::SystemData){
-        for(pos, rend, asteroid) in (&mut data.0, &mut data.1, &data.2).join(){
-            let radians = pos.rot.to_radians();
-
-            pos.x += asteroid.speed * radians.sin();
-            pos.y -= asteroid.speed * radians.cos();
-
-            let half_width = (rend.o_w / 2) as u32;
-            let half_height = (rend.o_h / 2) as u32;
-
-            if pos.x > (crate::GAME_WIDTH - half_width).into()
-                || pos.x < half_width.into() {
-                    pos.rot = 360.0 - pos.rot;
-            } else if pos.y > (crate::GAME_HEIGHT - half_height).into()
-                || pos.y < half_height.into(){
-                    if pos.rot > 180.0{
-                        pos.rot = 540.0 - pos.rot;
-                    } else {
-                        pos.rot = 180.0 - pos.rot;
-                    }
-            }
-
+        for(rend, asteroid) in (&mut data.0, &data.1).join(){
             rend.rot += asteroid.rot_speed;
             
             if rend.rot > 360.0 {
@@ -46,21 +30,223 @@ impl<'a> System<'a> for AsteroidMover{
 }
 
 
+// Counts down the tint after a hit, see components::HitFlash.
+pub struct HitFlashFader;
+
+impl<'a> System<'a> for HitFlashFader {
+    type SystemData = WriteStorage<'a, components::HitFlash>;
+
+    fn run(&mut self, mut flashes: Self::SystemData) {
+        for flash in (&mut flashes).join() {
+            flash.ticks = flash.ticks.saturating_sub(1);
+        }
+    }
+}
+
+// Slows down ships near a Chilling asteroid. Several ones close by don't add up, the strongest counts.
+pub struct AsteroidChiller;
+
+impl<'a> System<'a> for AsteroidChiller {
+    type SystemData = (
+        ReadStorage<'a, components::Position>,
+        WriteStorage<'a, components::Velocity>,
+        ReadStorage<'a, components::Player>,
+        ReadStorage<'a, components::Chilling>
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (positions, mut velocities, players, chillings) = data;
+
+        for (player_pos, velocity, _) in (&positions, &mut velocities, &players).join() {
+            let slowdown = (&positions, &chillings).join()
+                .filter(|(pos, chilling)| {
+                    let diff_x = pos.x - player_pos.x;
+                    let diff_y = pos.y - player_pos.y;
+                    (diff_x*diff_x) + (diff_y*diff_y) < chilling.radius * chilling.radius
+                })
+                .map(|(_, chilling)| chilling.slowdown)
+                .fold(1.0, f64::min);
+            velocity.0 *= slowdown;
+        }
+    }
+}
+
+// Mass of an asteroid, from the area of its sprite.
+fn mass(rend: &components::Renderable) -> f64 {
+    (rend.o_w * rend.o_w) as f64
+}
+
+// Elastic collisions between asteroids, only when Rules::asteroid_collisions is set.
+// Overlapping asteroids are pushed apart, the lighter one moving further, and exchange
+// momentum along the line between their centers.
+pub struct AsteroidBouncer;
+
+impl<'a> System<'a> for AsteroidBouncer {
+    type SystemData = (
+        WriteStorage<'a, components::Position>,
+        ReadStorage<'a, components::Renderable>,
+        ReadStorage<'a, components::Asteroid>,
+        WriteStorage<'a, components::Velocity>,
+        ReadExpect<'a, SpatialGrid>,
+        ReadExpect<'a, Rules>,
+        Entities<'a>
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut positions, rends, asteroids, mut velocities, grid, rules, entities) = data;
+
+        if !rules.asteroid_collisions {
+            return;
+        }
+
+        // Each pair once, the grid only finds candidates since positions change as pairs are resolved.
+        let mut pairs = Vec::<(Entity, Entity)>::new();
+        for (pos, rend, _, entity) in (&positions, &rends, &asteroids, &entities).join() {
+            grid.query(pos.x, pos.y, rend.o_w as f64 / 2.0, |other| {
+                if other.entity.id() > entity.id() {
+                    pairs.push((entity, other.entity));
+                }
+            });
+        }
+
+        for (first, second) in pairs {
+            let (first_pos, second_pos) = match (positions.get(first), positions.get(second)) {
+                (Some(first_pos), Some(second_pos)) => (Vector2D::new(first_pos.x, first_pos.y), Vector2D::new(second_pos.x, second_pos.y)),
+                _ => continue
+            };
+            let (first_rend, second_rend) = match (rends.get(first), rends.get(second)) {
+                (Some(first_rend), Some(second_rend)) => (first_rend, second_rend),
+                _ => continue
+            };
+            let (first_velocity, second_velocity) = match (velocities.get(first), velocities.get(second)) {
+                (Some(first_velocity), Some(second_velocity)) => (first_velocity.0, second_velocity.0),
+                _ => continue
+            };
+
+            let offset = second_pos - first_pos;
+            let distance = offset.length();
+            let overlap = (first_rend.o_w + second_rend.o_w) as f64 / 2.0 - distance;
+            // Exactly on top of each other there is no direction to push them apart in.
+            if overlap <= 0.0 || distance == 0.0 {
+                continue;
+            }
+
+            let normal = offset / distance;
+            let first_mass = mass(first_rend);
+            let second_mass = mass(second_rend);
+            let total_mass = first_mass + second_mass;
+
+            let first_push = normal * (overlap * second_mass / total_mass);
+            let second_push = normal * (overlap * first_mass / total_mass);
+
+            // Negative when they move towards each other. Already separating pairs only get pushed apart.
+            let approach = Vector2D::dot(second_velocity - first_velocity, normal);
+            let (first_velocity, second_velocity) = if approach < 0.0 {
+                (first_velocity + normal * (2.0 * second_mass / total_mass * approach),
+                 second_velocity - normal * (2.0 * first_mass / total_mass * approach))
+            } else {
+                (first_velocity, second_velocity)
+            };
+
+            if let Some(pos) = positions.get_mut(first) {
+                pos.x -= first_push.x;
+                pos.y -= first_push.y;
+            }
+            if let Some(pos) = positions.get_mut(second) {
+                pos.x += second_push.x;
+                pos.y += second_push.y;
+            }
+            if let Some(velocity) = velocities.get_mut(first) {
+                velocity.0 = first_velocity;
+            }
+            if let Some(velocity) = velocities.get_mut(second) {
+                velocity.0 = second_velocity;
+            }
+        }
+    }
+}
+
+// Pushes asteroids out of a raised shield, bouncing them off it, and absorbs the UFO shots that
+// reach it. The ship itself isn't moved.
+pub struct ShieldPusher;
+
+impl<'a> System<'a> for ShieldPusher {
+    type SystemData = (
+        WriteStorage<'a, components::Position>,
+        WriteStorage<'a, components::Velocity>,
+        ReadStorage<'a, components::Player>,
+        ReadStorage<'a, components::Missile>,
+        ReadExpect<'a, SpatialGrid>,
+        Entities<'a>
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut positions, mut velocities, players, missiles, grid, entities) = data;
+
+        let shields: Vec<(Vector2D<f64>, Vector2D<f64>)> = (&positions, &velocities, &players).join()
+            .filter(|(_, _, player)| player.shielded)
+            .map(|(pos, velocity, _)| (Vector2D::new(pos.x, pos.y), velocity.0))
+            .collect();
+
+        for (center, shield_velocity) in shields {
+            let mut touching = Vec::<(Entity, f64)>::new();
+            grid.query(center.x, center.y, crate::game::SHIELD_RADIUS, |candidate| {
+                touching.push((candidate.entity, candidate.radius));
+            });
+
+            for (asteroid_entity, radius) in touching {
+                let (pos, velocity) = match (positions.get_mut(asteroid_entity), velocities.get_mut(asteroid_entity)) {
+                    (Some(pos), Some(velocity)) => (pos, velocity),
+                    _ => continue
+                };
+
+                let offset = Vector2D::new(pos.x, pos.y) - center;
+                let distance = offset.length();
+                let reach = crate::game::SHIELD_RADIUS + radius;
+                if distance >= reach || distance == 0.0 {
+                    continue;
+                }
+
+                let normal = offset / distance;
+                pos.x = center.x + normal.x * reach;
+                pos.y = center.y + normal.y * reach;
+
+                // Only asteroids moving towards the shield bounce, the shield is much heavier.
+                let approach = Vector2D::dot(velocity.0 - shield_velocity, normal);
+                if approach < 0.0 {
+                    velocity.0 -= normal * (2.0 * approach);
+                }
+            }
+
+            for (pos, missile, entity) in (&positions, &missiles, &entities).join() {
+                let offset = Vector2D::new(pos.x, pos.y) - center;
+                if missile.hostile && offset.length() < crate::game::SHIELD_RADIUS {
+                    entities.delete(entity).ok();
+                }
+            }
+        }
+    }
+}
+
+// Destroys ships that touch an asteroid, a UFO or a UFO's shot.
 pub struct AsteroidCollider;
 
 impl<'a> System<'a> for AsteroidCollider {
     type SystemData = (
-        WriteStorage<'a, components::Position>,
-        WriteStorage<'a, components::Renderable>,
-        WriteStorage<'a, components::Player>,
-        WriteStorage<'a, components::Asteroid>,
+        ReadStorage<'a, components::Position>,
+        ReadStorage<'a, components::Renderable>,
+        ReadStorage<'a, components::Player>,
         WriteStorage<'a, components::GameData>,
+        ReadStorage<'a, components::Collider>,
+        ReadStorage<'a, components::Ufo>,
+        ReadStorage<'a, components::Missile>,
+        ReadExpect<'a, SpatialGrid>,
         Entities<'a>
     );
 
     fn run(&mut self, data: Self::SystemData) {
         
-        let(positions, rends, players, asteroids, gamedatas, entities) = data;
+        let(positions, rends, players, mut gamedatas, colliders, ufos, missiles, grid, entities) = data;
 
         for gamedata in (&gamedatas).join() {
             if gamedata.god_mode == true {
@@ -68,19 +254,64 @@ impl<'a> System<'a> for AsteroidCollider {
             }
         }
 
-        for (player_pos, player_rend, _, entity) in (&positions, &rends, &players, &entities).join() {
-            for(asteroid_pos, asteroid_rend, _) in (&positions, &rends, &asteroids).join(){
-                let diff_x: f64 = (player_pos.x - asteroid_pos.x).abs();
-                let diff_y: f64 = (player_pos.y - asteroid_pos.y).abs();
-                
-                // Get hypotenuse of positions.
-                let hyp: f64 = ((diff_x*diff_x) + (diff_y*diff_y)).sqrt();
+        let mut ships_lost: u32 = 0;
 
-                if hyp < (asteroid_rend.o_w + player_rend.o_w) as f64 / 2.0 {
-                    entities.delete(entity).ok();
+        for (player_pos, player_rend, player, entity) in (&positions, &rends, &players, &entities).join() {
+            // Freshly respawned ships get a moment to get away, a shield keeps everything off.
+            if player.invulnerable > 0 || player.shielded {
+                continue;
+            }
+
+            // Entities without a collider are treated as a circle filling the sprite.
+            let player_fallback = Shape::Circle{radius: player_rend.o_w as f64 / 2.0};
+            let player_shape = colliders.get(entity).map_or(&player_fallback, |collider| &collider.shape);
+            let player_center = Vector2D::new(player_pos.x, player_pos.y);
+
+            // A ship can only be destroyed once, even when it hits two asteroids at once.
+            let mut hit = false;
+            grid.query(player_pos.x, player_pos.y, player_shape.bounding_radius(), |candidate| {
+                if hit {
+                    return;
                 }
 
+                let asteroid_fallback = Shape::Circle{radius: candidate.radius};
+                let asteroid_shape = colliders.get(candidate.entity).map_or(&asteroid_fallback, |collider| &collider.shape);
+                let asteroid_rot = rends.get(candidate.entity).map_or(0.0, |rend| rend.rot);
+
+                hit = collision::shapes_overlap(player_shape, player_center, player_rend.rot,
+                                                asteroid_shape, Vector2D::new(candidate.x, candidate.y), asteroid_rot);
+            });
+
+            // There are only ever a few UFOs and shots of theirs, they aren't in the grid. Both
+            // are destroyed along with the ship.
+            if !hit {
+                for (pos, rend, other) in (&positions, &rends, &entities).join() {
+                    if !ufos.contains(other) && !missiles.get(other).is_some_and(|missile| missile.hostile) {
+                        continue;
+                    }
+
+                    let fallback = Shape::Circle{radius: rend.o_w as f64 / 2.0};
+                    let shape = colliders.get(other).map_or(&fallback, |collider| &collider.shape);
+                    if collision::shapes_overlap(player_shape, player_center, player_rend.rot, shape, Vector2D::new(pos.x, pos.y), rend.rot) {
+                        entities.delete(other).ok();
+                        hit = true;
+                        break;
+                    }
+                }
             }
+
+            if hit {
+                entities.delete(entity).ok();
+                ships_lost += 1;
+            }
+        }
+
+        if ships_lost == 0 {
+            return;
+        }
+
+        for gamedata in (&mut gamedatas).join() {
+            gamedata.lose_ships(ships_lost);
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,112 @@
+use specs::prelude::*;
+use specs::{Entities, Join};
+
+use crate::components;
+use crate::powerup::Drops;
+use crate::score::{self, Scoring};
+use crate::spatial::SpatialGrid;
+
+// Bombs a new ship comes with, and the most it can carry.
+pub const STARTING_BOMBS: u32 = 2;
+pub const MAX_BOMBS: u32 = 3;
+// Pixels per tick a shockwave's ring grows by.
+pub const SHOCKWAVE_SPEED: f64 = 24.0;
+// Enough to reach the corners of the arena from anywhere in it.
+pub const SHOCKWAVE_MAX_RADIUS: f64 = 1440.0;
+// The front end slows the game down until a shockwave has grown this big.
+const SLOW_MOTION_RADIUS: f64 = 480.0;
+
+// Sets off a smart bomb at the given point. Its ring destroys every asteroid it reaches, see
+// ShockwaveBlaster.
+pub fn spawn_shockwave(ecs: &mut World, x: f64, y: f64) -> Entity {
+    ecs.create_entity()
+        .with(components::Position{x, y, rot: 0.0})
+        .with(components::Renderable{
+            tex_name: String::from("img/shockwave.png"),
+            i_w: 256,
+            i_h: 256,
+            o_w: 0,
+            o_h: 0,
+            frame: 0,
+            total_frames: 1,
+            rot: 0.0
+        })
+        .with(components::Shockwave{radius: 0.0, max_radius: SHOCKWAVE_MAX_RADIUS})
+        .build()
+}
+
+// A bomb just went off, the game should run in slow motion for a moment.
+pub fn slow_motion(ecs: &World) -> bool {
+    let shockwaves = ecs.read_storage::<components::Shockwave>();
+    shockwaves.join().any(|shockwave| shockwave.radius < SLOW_MOTION_RADIUS)
+}
+
+// Grows the shockwaves' rings along with their sprites, and deletes them once they're done.
+pub struct ShockwaveExpander;
+
+impl<'a> System<'a> for ShockwaveExpander {
+    type SystemData = (
+        WriteStorage<'a, components::Shockwave>,
+        WriteStorage<'a, components::Renderable>,
+        Entities<'a>
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut shockwaves, mut rends, entities) = data;
+
+        for (shockwave, rend, entity) in (&mut shockwaves, &mut rends, &entities).join() {
+            shockwave.radius += SHOCKWAVE_SPEED;
+            if shockwave.radius > shockwave.max_radius {
+                entities.delete(entity).ok();
+            }
+            rend.o_w = (shockwave.radius * 2.0) as u32;
+            rend.o_h = rend.o_w;
+        }
+    }
+}
+
+// Destroys every asteroid a shockwave's ring has reached, whole and scored as if the player had
+// shot it. Runs before missile::MissileStriker, which leaves alone the asteroids it has taken.
+pub struct ShockwaveBlaster;
+
+impl<'a> System<'a> for ShockwaveBlaster {
+    type SystemData = (
+        ReadStorage<'a, components::Position>,
+        ReadStorage<'a, components::Renderable>,
+        ReadStorage<'a, components::Shockwave>,
+        WriteStorage<'a, components::Asteroid>,
+        ReadExpect<'a, SpatialGrid>,
+        WriteExpect<'a, Drops>,
+        WriteExpect<'a, Scoring>,
+        Entities<'a>
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (positions, rends, shockwaves, mut asteroids, grid, mut drops, mut scoring, entities) = data;
+
+        for (shockwave_pos, shockwave) in (&positions, &shockwaves).join() {
+            let mut touched = Vec::<Entity>::new();
+            grid.query(shockwave_pos.x, shockwave_pos.y, shockwave.radius, |candidate| {
+                let diff_x = candidate.x - shockwave_pos.x;
+                let diff_y = candidate.y - shockwave_pos.y;
+                if ((diff_x*diff_x) + (diff_y*diff_y)).sqrt() <= shockwave.radius + candidate.radius {
+                    touched.push(candidate.entity);
+                }
+            });
+
+            for asteroid_entity in touched {
+                // The asteroid is only deleted once the tick is over, without its Asteroid nothing
+                // else hits it in the meantime, and a second shockwave can't score it again.
+                if asteroids.remove(asteroid_entity).is_none() {
+                    continue;
+                }
+                entities.delete(asteroid_entity).ok();
+
+                if let (Some(pos), Some(rend)) = (positions.get(asteroid_entity), rends.get(asteroid_entity)) {
+                    scoring.kills.push((pos.x, pos.y, score::asteroid_points(rend.o_w)));
+                    drops.positions.push((pos.x, pos.y));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use std::fs::File;
+use std::path::Path;
+
+use vector2d::Vector2D;
+
+// Swept point-vs-circle test: where along the segment from start to end a point first
+// touches a circle around the origin, as a fraction from 0 (start) to 1 (end).
+// Both ends are relative to the circle's center, so a moving circle is handled by
+// subtracting its own start and end positions.
+pub fn segment_hits_circle(start: Vector2D<f64>, end: Vector2D<f64>, radius: f64) -> Option<f64> {
+    let c = Vector2D::dot(start, start) - radius*radius;
+    // Already inside at the start of the tick.
+    if c < 0.0 {
+        return Some(0.0);
+    }
+
+    let direction = end - start;
+    let a = Vector2D::dot(direction, direction);
+    if a == 0.0 {
+        return None;
+    }
+
+    // Solve |start + t * direction| = radius for the smaller t.
+    let b = 2.0 * Vector2D::dot(start, direction);
+    let discriminant = b*b - 4.0*a*c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let t = (-b - discriminant.sqrt()) / (2.0 * a);
+    if (0.0..=1.0).contains(&t) {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+// Most points kept in a hull generated from a sprite, so SAT tests stay cheap.
+pub const MAX_HULL_POINTS: usize = 12;
+// Pixels with at least this alpha count as solid.
+pub const ALPHA_THRESHOLD: u8 = 128;
+
+// Collision shape in the entity's local space, centered on its Position and turned with
+// Renderable.rot like the sprite. Uses screen coordinates, y pointing down, with the sprite
+// facing up at a rotation of 0.
+#[derive(Clone)]
+pub enum Shape {
+    Circle { radius: f64 },
+    // Rectangle given by half its width and height
+    Box { half_width: f64, half_height: f64 },
+    // Convex polygon, points in order around the center
+    Polygon { points: Vec<Vector2D<f64>> }
+}
+
+impl Shape {
+    // Radius of a circle around the center containing the whole shape, for broad-phase queries.
+    pub fn bounding_radius(&self) -> f64 {
+        match self {
+            Shape::Circle { radius } => *radius,
+            Shape::Box { half_width, half_height } => (half_width*half_width + half_height*half_height).sqrt(),
+            Shape::Polygon { points } => points.iter().map(|point| point.length()).fold(0.0, f64::max)
+        }
+    }
+
+    // Corners in world space, None for a circle.
+    fn world_points(&self, position: Vector2D<f64>, rot: f64) -> Option<Vec<Vector2D<f64>>> {
+        let local = match self {
+            Shape::Circle { .. } => return None,
+            Shape::Box { half_width, half_height } => vec![
+                Vector2D::new(-half_width, -half_height),
+                Vector2D::new(*half_width, -half_height),
+                Vector2D::new(*half_width, *half_height),
+                Vector2D::new(-half_width, *half_height)
+            ],
+            Shape::Polygon { points } => points.clone()
+        };
+
+        // Clockwise on screen, like SDL rotates the sprite.
+        let (sin, cos) = rot.to_radians().sin_cos();
+        Some(local.iter()
+            .map(|point| position + Vector2D::new(point.x * cos - point.y * sin, point.x * sin + point.y * cos))
+            .collect())
+    }
+}
+
+// Separating axis test between two shapes placed in the world. Shapes that only touch don't overlap.
+pub fn shapes_overlap(first: &Shape, first_position: Vector2D<f64>, first_rot: f64,
+                      second: &Shape, second_position: Vector2D<f64>, second_rot: f64) -> bool {
+    match (first.world_points(first_position, first_rot), second.world_points(second_position, second_rot)) {
+        (None, None) => {
+            let reach = first.bounding_radius() + second.bounding_radius();
+            let offset = second_position - first_position;
+            Vector2D::dot(offset, offset) < reach*reach
+        },
+        (Some(points), None) => polygon_overlaps_circle(&points, second_position, second.bounding_radius()),
+        (None, Some(points)) => polygon_overlaps_circle(&points, first_position, first.bounding_radius()),
+        (Some(first_points), Some(second_points)) => {
+            edge_normals(&first_points).chain(edge_normals(&second_points))
+                .all(|axis| {
+                    let (first_min, first_max) = project(&first_points, axis);
+                    let (second_min, second_max) = project(&second_points, axis);
+                    first_max > second_min && second_max > first_min
+                })
+        }
+    }
+}
+
+fn polygon_overlaps_circle(points: &[Vector2D<f64>], center: Vector2D<f64>, radius: f64) -> bool {
+    // Besides the edges, the axis towards the closest corner separates a circle next to a corner.
+    let closest = points.iter()
+        .copied()
+        .min_by(|a, b| (*a - center).length_squared().total_cmp(&(*b - center).length_squared()));
+    let corner_axis = closest.map(|corner| corner - center).filter(|axis| axis.length_squared() > 0.0);
+
+    edge_normals(points).chain(corner_axis)
+        .all(|axis| {
+            let (min, max) = project(points, axis);
+            let center_projection = Vector2D::dot(center, axis);
+            let reach = radius * axis.length();
+            max > center_projection - reach && center_projection + reach > min
+        })
+}
+
+fn edge_normals(points: &[Vector2D<f64>]) -> impl Iterator<Item = Vector2D<f64>> + '_ {
+    (0..points.len()).map(move |i| {
+        let edge = points[(i + 1) % points.len()] - points[i];
+        Vector2D::new(-edge.y, edge.x)
+    })
+}
+
+fn project(points: &[Vector2D<f64>], axis: Vector2D<f64>) -> (f64, f64) {
+    points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), point| {
+        let projection = Vector2D::dot(*point, axis);
+        (min.min(projection), max.max(projection))
+    })
+}
+
+// Convex hull around the solid pixels of a sprite, given as one alpha value per pixel row by row.
+// Points are relative to the sprite's center and scaled to its size, from -0.5 to 0.5, so the hull
+// can be stretched to any output size. Empty if the sprite has no solid pixels.
+pub fn hull_from_alpha(width: u32, height: u32, alpha: &[u8]) -> Vec<Vector2D<f64>> {
+    // Only the outer corners of the leftmost and rightmost solid pixel in each row can be on the hull.
+    let mut corners = Vec::new();
+    for y in 0..height {
+        let row = &alpha[(y * width) as usize..((y + 1) * width) as usize];
+        let first = row.iter().position(|value| *value >= ALPHA_THRESHOLD);
+        let last = row.iter().rposition(|value| *value >= ALPHA_THRESHOLD);
+        if let (Some(first), Some(last)) = (first, last) {
+            for (x, y) in [(first, y), (first, y + 1), (last + 1, y), (last + 1, y + 1)] {
+                corners.push(Vector2D::new(x as f64, y as f64));
+            }
+        }
+    }
+
+    let mut hull = convex_hull(corners);
+
+    // Drop the corners that add the least area until the hull is small enough.
+    while hull.len() > MAX_HULL_POINTS {
+        let count = hull.len();
+        let smallest = (0..count)
+            .min_by(|a, b| {
+                let area = |i: usize| triangle_area(hull[(i + count - 1) % count], hull[i], hull[(i + 1) % count]);
+                area(*a).total_cmp(&area(*b))
+            })
+            .unwrap_or(0);
+        hull.remove(smallest);
+    }
+
+    hull.iter()
+        .map(|point| Vector2D::new(point.x / width as f64 - 0.5, point.y / height as f64 - 0.5))
+        .collect()
+}
+
+// Convex hull around the solid pixels of a PNG image, for collision shapes that match the sprite.
+// Decodes the file itself instead of going through SDL_image, so headless runs get the same hulls
+// without SDL.
+pub fn load_hull(path: &Path) -> Result<Vec<Vector2D<f64>>, String> {
+    let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut decoder = png::Decoder::new(file);
+    // Palette and low bit depth images are expanded to 8 bits per channel, with tRNS as alpha.
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut pixels).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let (width, height) = (frame.width, frame.height);
+    let channels = frame.color_type.samples();
+    let alpha: Vec<u8> = (0..height as usize)
+        .flat_map(|y| (0..width as usize).map(move |x| y * frame.line_size + x * channels))
+        .map(|offset| match frame.color_type {
+            png::ColorType::Rgba => pixels[offset + 3],
+            png::ColorType::GrayscaleAlpha => pixels[offset + 1],
+            _ => u8::MAX
+        })
+        .collect();
+
+    Ok(hull_from_alpha(width, height, &alpha))
+}
+
+// Andrew's monotone chain, returns the hull without collinear points.
+fn convex_hull(mut points: Vec<Vector2D<f64>>) -> Vec<Vector2D<f64>> {
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup_by(|a, b| a.x == b.x && a.y == b.y);
+    if points.len() < 3 {
+        return points;
+    }
+
+    // Lower and upper half, the last point of each is the first one of the other.
+    let mut hull = half_hull(points.iter());
+    let mut upper = half_hull(points.iter().rev());
+    hull.pop();
+    upper.pop();
+    hull.append(&mut upper);
+    hull
+}
+
+fn half_hull<'p>(points: impl Iterator<Item = &'p Vector2D<f64>>) -> Vec<Vector2D<f64>> {
+    let mut half: Vec<Vector2D<f64>> = Vec::new();
+    for point in points {
+        while half.len() >= 2 && cross(half[half.len() - 2], half[half.len() - 1], *point) <= 0.0 {
+            half.pop();
+        }
+        half.push(*point);
+    }
+    half
+}
+
+fn cross(origin: Vector2D<f64>, a: Vector2D<f64>, b: Vector2D<f64>) -> f64 {
+    (a.x - origin.x) * (b.y - origin.y) - (a.y - origin.y) * (b.x - origin.x)
+}
+
+fn triangle_area(a: Vector2D<f64>, b: Vector2D<f64>, c: Vector2D<f64>) -> f64 {
+    cross(a, b, c).abs() / 2.0
+}
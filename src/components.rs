@@ -1,15 +1,30 @@
 use specs::prelude::*;
 use specs_derive::Component;
+use serde::Deserialize;
 use vector2d::Vector2D;
 
+use crate::collision::Shape;
+
 
 #[derive(Component)]
 pub struct Position{
     pub x: f64,
     pub y: f64,
+    // Direction the entity faces, independent of where it's moving
     pub rot: f64
 }
 
+// Where the entity was before the last movement::Mover step, for swept collision tests.
+#[derive(Component)]
+pub struct PreviousPosition {
+    pub x: f64,
+    pub y: f64
+}
+
+// Pixels per tick, y pointing down like the screen. Applied to Position by movement::Mover.
+#[derive(Component)]
+pub struct Velocity(pub Vector2D<f64>);
+
 #[derive(Component)]
 pub struct Renderable {
     // Texture name
@@ -30,33 +45,241 @@ pub struct Renderable {
     pub rot: f64
 }
 
+// Shape used for collision tests, see collision::shapes_overlap.
+#[derive(Component, Clone)]
+pub struct Collider {
+    pub shape: Shape
+}
+
+// What happens when an entity reaches the edge of the arena, handled by movement::Boundaries.
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub enum BoundaryBehavior {
+    // Leaves on one side and comes back on the other
+    Wrap,
+    // Turns around when its sprite touches the edge
+    Bounce,
+    // Is deleted once its center is outside
+    Despawn,
+    // Stops at the edge
+    Clamp
+}
+
+impl BoundaryBehavior {
+    pub fn from_name(name: &str) -> Option<BoundaryBehavior> {
+        match name {
+            "wrap" => Some(BoundaryBehavior::Wrap),
+            "bounce" => Some(BoundaryBehavior::Bounce),
+            "despawn" => Some(BoundaryBehavior::Despawn),
+            "clamp" => Some(BoundaryBehavior::Clamp),
+            _ => None
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BoundaryBehavior::Wrap => "wrap",
+            BoundaryBehavior::Bounce => "bounce",
+            BoundaryBehavior::Despawn => "despawn",
+            BoundaryBehavior::Clamp => "clamp"
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct Player {
+    // Thrust applied this tick, added to the Velocity
     pub impulse: Vector2D<f64>,
-    pub cur_speed: Vector2D<f64>
+    // Ticks left in which asteroids can't destroy the ship
+    pub invulnerable: u32,
+    // Ticks until the weapon can fire again
+    pub fire_cooldown: u32,
+    // Index of the current weapon in weapons::Weapons
+    pub weapon: usize,
+    // Shield energy up to game::SHIELD_ENERGY, drained while the shield is up and recharged while it's down
+    pub shield: u32,
+    // The shield is up this tick, see asteroid::ShieldPusher
+    pub shielded: bool,
+    // The shield ran dry and Shield hasn't been released since, see game::SHIELD_MIN_ENERGY
+    pub shield_depleted: bool,
+    // Ticks until the next hyperspace jump
+    pub hyperspace_cooldown: u32,
+    // Smart bombs left, see bomb::spawn_shockwave
+    pub bombs: u32
 }
 
 #[derive(Component)]
 pub struct Asteroid {
-    pub speed: f64,
-    pub rot_speed: f64
+    pub rot_speed: f64,
+    // Name of its kind in archetypes::Archetypes
+    pub kind: String
 }
 
+// Hit points, the entity is destroyed once they reach 0.
+#[derive(Component)]
+pub struct Health {
+    pub current: u32,
+    pub max: u32
+}
+
+// Damages the asteroids within radius when the asteroid is destroyed, see missile::MissileStriker.
+#[derive(Component, Deserialize, Clone, Debug)]
+pub struct Explosive {
+    pub radius: f64,
+    pub damage: u32
+}
+
+// Slows down the ship while it's within radius, see asteroid::AsteroidChiller.
+#[derive(Component, Deserialize, Clone, Debug)]
+pub struct Chilling {
+    pub radius: f64,
+    // Part of its velocity the ship keeps each tick
+    pub slowdown: f64
+}
+
+// Ticks left in which the sprite is drawn tinted, after taking a hit that didn't destroy it.
+#[derive(Component)]
+pub struct HitFlash {
+    pub ticks: u32
+}
+
+// A shot from one of the ship's weapons, or from a UFO.
 #[derive(Component)]
 pub struct Missile {
-    pub speed: f64,
+    // Name of the weapon that fired it
+    pub weapon: String,
+    // Fired by a UFO, it destroys the ship and scores nothing
+    pub hostile: bool,
+    pub damage: u32,
+    // Flies on through what it hits
+    pub piercing: bool,
+    // Asteroids a piercing shot is already going through, each one is only damaged once
+    pub pierced: Vec<Entity>
+}
+
+// Steers a missile towards the nearest asteroid, see missile::MissileHoming.
+#[derive(Component)]
+pub struct Homing {
+    // Degrees per tick
+    pub turn_rate: f64
+}
+
+// Remaining time and distance before the entity is deleted by movement::Expiry,
+// whichever runs out first. A limit left as None never runs out.
+#[derive(Component)]
+pub struct Lifetime {
+    pub ticks: Option<u32>,
+    pub distance: Option<f64>
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum UfoSize {
+    // Slow and shoots anywhere
+    Large,
+    // Fast and aims at the ship
+    Small
+}
+
+// A flying saucer, moved and fired by ufo::update_ufos.
+#[derive(Component)]
+pub struct Ufo {
+    pub size: UfoSize,
+    // Ticks until it changes its vertical direction
+    pub turn_timer: u32,
+    // Ticks until it shoots
+    pub fire_timer: u32
+}
+
+// A smart bomb's ring, growing until it reaches max_radius. See bomb::ShockwaveExpander.
+#[derive(Component)]
+pub struct Shockwave {
+    pub radius: f64,
+    pub max_radius: f64
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PowerUpKind {
+    RapidFire,
+    TripleShot,
+    ShieldRecharge,
+    ExtraLife,
+    ScoreMultiplier,
+    Bomb
+}
+
+// A pickup dropped by a destroyed asteroid, collected by flying into it. See powerup::PowerUpCollector.
+#[derive(Component)]
+pub struct PowerUp {
+    pub kind: PowerUpKind
+}
+
+// Ticks left of each timed power-up the ship picked up, 0 when it isn't active. Lost with the ship.
+#[derive(Component, Default)]
+pub struct ActiveEffects {
+    pub rapid_fire: u32,
+    pub triple_shot: u32,
+    pub score_multiplier: u32
+}
+
+// Hits the ship's shots made in a row, see score::ScoreKeeper. Lost with the ship.
+#[derive(Component, Default)]
+pub struct Combo {
+    pub hits: u32,
+    // Ticks left to land the next hit in
+    pub ticks: u32
+}
+
+// Points floating up from where they were made, drawn as text by the front end.
+#[derive(Component)]
+pub struct ScorePopup {
+    pub points: u32
 }
 
 pub struct PendingAsteroid {
     pub x: f64,
     pub y: f64,
-    pub rot: f64,
-    pub size: u32
+    pub velocity: Vector2D<f64>,
+    pub size: u32,
+    pub kind: String
 }
 
 #[derive(Component)]
 pub struct GameData {
     pub score: u32,
     pub level: u32,
-    pub god_mode: bool
-}
\ No newline at end of file
+    pub god_mode: bool,
+    // Ships left, including the one currently flying
+    pub lives: u32,
+    // Score at which the next extra life is awarded
+    pub next_extra_life: u32,
+    // Ticks until a destroyed ship may respawn
+    pub respawn_timer: u32,
+    // Ticks until the next UFO appears, only counted down while there's a ship and no UFO
+    pub ufo_timer: u32,
+    // Set when the last ship was destroyed, the final score is kept until the game restarts
+    pub game_over: bool
+}
+
+impl GameData {
+    // A new game at level 1, with no score yet and every ship left.
+    pub fn new(god_mode: bool) -> Self {
+        GameData {
+            score: 0,
+            level: 1,
+            god_mode,
+            lives: crate::game::STARTING_LIVES,
+            next_extra_life: crate::game::EXTRA_LIFE_SCORE,
+            respawn_timer: 0,
+            ufo_timer: crate::ufo::UFO_INTERVAL,
+            game_over: false
+        }
+    }
+
+    // Takes away destroyed ships and starts the wait for the next one, the game is over once none are left.
+    pub fn lose_ships(&mut self, count: u32) {
+        self.lives = self.lives.saturating_sub(count);
+        self.respawn_timer = crate::game::RESPAWN_DELAY;
+        if self.lives == 0 {
+            self.game_over = true;
+        }
+    }
+}
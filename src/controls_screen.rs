@@ -0,0 +1,91 @@
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::render::TextureCreator;
+use sdl2::ttf::Font;
+use sdl2::video::WindowContext;
+
+use asteroid_game::input::{Action, InputMap};
+
+use crate::ui::{self, UIElement};
+
+// Lets the player pick an action with the arrow keys and press Enter to bind a new key to it.
+pub struct ControlsScreen<'a> {
+    selected: usize,
+    waiting_for_key: bool,
+    message: String,
+    ui_elements: Vec<UIElement<'a>>,
+    // The text only needs to be rendered again after something changed.
+    dirty: bool
+}
+
+impl<'a> ControlsScreen<'a> {
+    pub fn new() -> Self {
+        ControlsScreen {
+            selected: 0,
+            waiting_for_key: false,
+            message: String::new(),
+            ui_elements: Vec::new(),
+            dirty: true
+        }
+    }
+
+    // Returns true when the screen should be closed.
+    pub fn handle_key(&mut self, key: Keycode, input_map: &mut InputMap) -> bool {
+        self.dirty = true;
+
+        if self.waiting_for_key {
+            self.waiting_for_key = false;
+            self.message = match key {
+                Keycode::Escape => "Cancelled".to_string(),
+                Keycode::F1 => "F1 is reserved for this screen".to_string(),
+                _ => match input_map.bind(Action::ALL[self.selected], &key.name()) {
+                    Ok(()) => String::new(),
+                    Err(e) => e
+                }
+            };
+            return false;
+        }
+
+        match key {
+            Keycode::Up => self.selected = (self.selected + Action::ALL.len() - 1) % Action::ALL.len(),
+            Keycode::Down => self.selected = (self.selected + 1) % Action::ALL.len(),
+            Keycode::Return => self.waiting_for_key = true,
+            Keycode::Escape | Keycode::F1 => return true,
+            _ => {}
+        }
+        false
+    }
+
+    pub fn ui_elements(&mut self, font: &Font, texture_creator: &'a TextureCreator<WindowContext>, input_map: &InputMap) -> Result<&Vec<UIElement<'a>>, String> {
+        if !self.dirty {
+            return Ok(&self.ui_elements);
+        }
+
+        self.ui_elements.clear();
+        let color = Color::RGBA(15, 180, 75, 255);
+        let highlight = Color::RGBA(240, 220, 60, 255);
+
+        self.ui_elements.push(ui::text_element(font, texture_creator, "Controls", color, 400, 60, 60)?);
+
+        for (i, action) in Action::ALL.iter().enumerate() {
+            let key = if self.waiting_for_key && i == self.selected {
+                "press a key..."
+            } else {
+                input_map.key_for(*action).unwrap_or("-")
+            };
+            let line = format!("{}: {}", action.name(), key);
+            let line_color = if i == self.selected { highlight } else { color };
+            self.ui_elements.push(ui::text_element(font, texture_creator, &line, line_color, 400, 140 + 40 * i as i32, 36)?);
+        }
+
+        let help = if self.message.is_empty() {
+            "Up/Down to select, Enter to rebind, Escape to save and close"
+        } else {
+            &self.message
+        };
+        self.ui_elements.push(ui::text_element(font, texture_creator, help, color, 400, 160 + 40 * Action::ALL.len() as i32, 30)?);
+
+        self.dirty = false;
+        Ok(&self.ui_elements)
+    }
+}
@@ -1,20 +1,172 @@
-use std::collections::HashMap;
-use specs::{World, WorldExt, Join, Builder};
+use specs::{World, WorldExt, Join, Builder, Dispatcher, DispatcherBuilder, Entity};
 use vector2d::Vector2D;
 use rand::Rng;
 
 
 use crate::asteroid;
+use crate::bomb;
 use crate::components;
-use crate::components::GameData;
-use crate::utils;
+use crate::missile;
+use crate::movement;
+use crate::spatial;
+use crate::ufo;
+use crate::powerup;
+use crate::score;
+use crate::resources::{Arena, GameRng, Hulls, Rules};
+use crate::collision::Shape;
+use crate::input::{Action, InputState};
+use crate::weapons::{Weapon, Weapons};
+use crate::archetypes::{Archetype, Archetypes};
 
 const ROTATION_SPEED: f64 = 2.5;
 const PLAYER_SPEED: f64 = 3.5;
 
+pub const STARTING_LIVES: u32 = 3;
+// Score between extra lives, like the original.
+pub const EXTRA_LIFE_SCORE: u32 = 10000;
+// Ticks between losing a ship and the next one appearing, at the earliest.
+pub const RESPAWN_DELAY: u32 = 90;
+// A new ship only appears once no asteroid is this close to the center.
+const RESPAWN_CLEAR_RADIUS: f64 = 150.0;
+// Ticks a new ship can't be hit, it blinks during that time.
+pub const INVULNERABLE_TICKS: u32 = 180;
+// Pixels around the ship's center the shield keeps clear.
+pub const SHIELD_RADIUS: f64 = 48.0;
+// Energy of a full shield. It's used up 3 per tick while the shield is up, so it lasts 3 seconds,
+// and regained 1 per tick while it's down, so it takes 9 seconds to fill up.
+pub const SHIELD_ENERGY: u32 = 540;
+const SHIELD_DRAIN: u32 = 3;
+// Energy a shield that ran dry needs before it comes back up while Shield is still held.
+pub const SHIELD_MIN_ENERGY: u32 = 90;
+// Ticks between hyperspace jumps.
+pub const HYPERSPACE_COOLDOWN: u32 = 120;
+// Chance of a jump destroying the ship.
+pub const HYPERSPACE_FAILURE_CHANCE: f64 = 1.0 / 16.0;
+// Random spots tried for a jump, the one furthest from any asteroid or UFO is taken.
+const HYPERSPACE_TRIES: u32 = 12;
+
+pub fn register_components(ecs: &mut World){
+    ecs.register::<components::Position>();
+    ecs.register::<components::Velocity>();
+    ecs.register::<components::PreviousPosition>();
+    ecs.register::<components::BoundaryBehavior>();
+    ecs.register::<components::Collider>();
+    ecs.register::<components::Renderable>();
+    ecs.register::<components::Player>();
+    ecs.register::<components::Asteroid>();
+    ecs.register::<components::Health>();
+    ecs.register::<components::HitFlash>();
+    ecs.register::<components::Explosive>();
+    ecs.register::<components::Chilling>();
+    ecs.register::<components::Missile>();
+    ecs.register::<components::Homing>();
+    ecs.register::<components::Lifetime>();
+    ecs.register::<components::Ufo>();
+    ecs.register::<components::PowerUp>();
+    ecs.register::<components::ActiveEffects>();
+    ecs.register::<components::Shockwave>();
+    ecs.register::<components::Combo>();
+    ecs.register::<components::ScorePopup>();
+    ecs.register::<components::GameData>();
+}
+
+// Creates a World with every component registered and the shared resources inserted.
+pub fn create_world(arena: Arena, seed: u64) -> World {
+    let mut ecs = World::new();
+    register_components(&mut ecs);
+    ecs.insert(arena);
+    ecs.insert(GameRng::new(seed));
+    ecs.insert(Rules::default());
+    ecs.insert(Hulls::default());
+    ecs.insert(Weapons::default());
+    ecs.insert(Archetypes::default());
+    ecs.insert(spatial::SpatialGrid::default());
+    ecs.insert(powerup::Drops::default());
+    ecs.insert(score::Scoring::default());
+    ecs
+}
+
+// Systems that run once per fixed step after update.
+pub fn build_dispatcher<'a, 'b>() -> Dispatcher<'a, 'b> {
+    DispatcherBuilder::new()
+        .with(movement::Mover, "mover", &[])
+        .with(movement::Boundaries, "boundaries", &["mover"])
+        .with(movement::Expiry, "expiry", &["boundaries"])
+        .with(asteroid::HitFlashFader, "hit_flash_fader", &[])
+        .with(asteroid::AsteroidChiller, "asteroid_chiller", &["boundaries"])
+        .with(asteroid::AsteroidSpinner, "asteroid_spinner", &[])
+        .with(spatial::SpatialGridBuilder, "spatial_grid", &["boundaries"])
+        .with(asteroid::AsteroidBouncer, "asteroid_bouncer", &["spatial_grid"])
+        .with(asteroid::ShieldPusher, "shield_pusher", &["asteroid_bouncer"])
+        .with(asteroid::AsteroidCollider, "asteroid_collider", &["shield_pusher"])
+        .with(bomb::ShockwaveExpander, "shockwave_expander", &[])
+        .with(bomb::ShockwaveBlaster, "shockwave_blaster", &["spatial_grid", "shockwave_expander"])
+        .with(missile::MissileStriker, "missile_striker", &["asteroid_bouncer", "shockwave_blaster"])
+        .with(missile::MissileHoming, "missile_homing", &["missile_striker"])
+        .with(ufo::UfoHitter, "ufo_hitter", &["missile_striker"])
+        .with(powerup::EffectTimer, "effect_timer", &[])
+        .with(powerup::PowerUpCollector, "powerup_collector", &["ufo_hitter"])
+        .with(score::ScoreKeeper, "score_keeper", &["ufo_hitter"])
+        .build()
+}
+
+// Simulates one fixed step: player input, then every system, then the input's per tick state is reset.
+pub fn step(ecs: &mut World, dispatcher: &mut Dispatcher, input: &mut InputState){
+    update(ecs, input);
+    dispatcher.dispatch(ecs);
+    ecs.maintain();
+    input.end_tick();
+}
+
 pub fn load_world(ecs: &mut World){
+    spawn_player(ecs, components::Position{x: 650.0, y:250.0, rot: 0.0}, 0);
+
+    create_asteroid(ecs, components::Position{x: 400.0, y: 235.0, rot: 45.0}, 64);
+
     ecs.create_entity()
-        .with(crate::components::Position{x: 650.0, y:250.0, rot: 0.0})
+        .with(crate::components::GameData::new(false))
+        .build();
+
+}
+
+// Throws away the current game and starts a new one. The RNG is reseeded so every game
+// with the same seed plays out the same.
+pub fn new_game(ecs: &mut World){
+    let seed = ecs.read_resource::<GameRng>().seed;
+    ecs.insert(GameRng::new(seed));
+    // Asteroids destroyed in the last tick of the old game mustn't drop power-ups into the new one,
+    // or score in it.
+    ecs.insert(powerup::Drops::default());
+    ecs.insert(score::Scoring::default());
+
+    ecs.delete_all();
+    ecs.maintain();
+    load_world(ecs);
+}
+
+pub fn is_game_over(ecs: &World) -> bool {
+    let gamedatas = ecs.read_storage::<components::GameData>();
+    gamedatas.join().any(|gamedata| gamedata.game_over)
+}
+
+// The sprite's hull stretched to its output size if one was loaded, otherwise the fallback shape.
+pub fn collider_for(ecs: &World, tex_name: &str, o_w: u32, o_h: u32, fallback: Shape) -> components::Collider {
+    let hulls = ecs.read_resource::<Hulls>();
+    let shape = match hulls.shapes.get(tex_name) {
+        Some(hull) if hull.len() >= 3 => Shape::Polygon {
+            points: hull.iter().map(|point| Vector2D::new(point.x * o_w as f64, point.y * o_h as f64)).collect()
+        },
+        _ => fallback
+    };
+    components::Collider{shape}
+}
+
+pub fn spawn_player(ecs: &mut World, position: components::Position, invulnerable: u32){
+    let edges = ecs.read_resource::<Rules>().ship_edges;
+    let collider = collider_for(ecs, "img/ship.png", 64, 64, Shape::Circle{radius: 32.0});
+
+    ecs.create_entity()
+        .with(position)
         .with(crate::components::Renderable{
             tex_name: String::from("img/ship.png"),
             i_w: 256,
@@ -25,51 +177,98 @@ pub fn load_world(ecs: &mut World){
             total_frames: 1,
             rot: 0.0
         })
+        .with(crate::components::Velocity(Vector2D::new(0.0, 0.0)))
+        .with(edges)
+        .with(collider)
         .with(crate::components::Player{
             impulse: Vector2D::new(0.0, 0.0),
-            cur_speed: Vector2D::new(0.0, 0.0)
+            invulnerable,
+            fire_cooldown: 0,
+            weapon: 0,
+            shield: SHIELD_ENERGY,
+            shielded: false,
+            shield_depleted: false,
+            hyperspace_cooldown: 0,
+            bombs: bomb::STARTING_BOMBS
         })
+        .with(components::ActiveEffects::default())
+        .with(components::Combo::default())
         .build();
+}
 
-    create_asteroid(ecs, components::Position{x: 400.0, y: 235.0, rot: 45.0}, 64);
+// Puts a new ship in the center once it's clear of asteroids. Returns false if it had to wait.
+fn try_respawn_player(ecs: &mut World) -> bool {
+    let (center_x, center_y) = {
+        let arena = ecs.read_resource::<Arena>();
+        (arena.width as f64 / 2.0, arena.height as f64 / 2.0)
+    };
 
-    ecs.create_entity()
-        .with(crate::components::GameData{score: 0, level: 1, god_mode: false})
-        .build();
+    {
+        let positions = ecs.read_storage::<components::Position>();
+        let asteroids = ecs.read_storage::<components::Asteroid>();
+
+        for (pos, _) in (&positions, &asteroids).join() {
+            let diff_x = pos.x - center_x;
+            let diff_y = pos.y - center_y;
+            if ((diff_x*diff_x) + (diff_y*diff_y)).sqrt() < RESPAWN_CLEAR_RADIUS {
+                return false;
+            }
+        }
+    }
 
+    spawn_player(ecs, components::Position{x: center_x, y: center_y, rot: 0.0}, INVULNERABLE_TICKS);
+    true
 }
 
-pub fn update(ecs: &mut World, key_manager: &mut HashMap<String, bool>){
+pub fn update(ecs: &mut World, input: &InputState){
 
-    let mut must_reload_world = false;
+    let mut player_alive = false;
     let mut current_player_position = components::Position{x: 0.0, y: 0.0, rot: 0.0};
 
     {
         let players = ecs.read_storage::<crate::components::Player>();
         let positions = ecs.read_storage::<crate::components::Position>();
 
-        for(pos, player) in (&positions, &players).join() {
+        for(pos, _) in (&positions, &players).join() {
             current_player_position.x = pos.x;
             current_player_position.y = pos.y;
+            player_alive = true;
         }
+    }
 
-        if players.join().count() < 1 {
-            must_reload_world = true;
+    if !player_alive {
+        let mut game_over = false;
+        let mut can_respawn = false;
+        {
+            let mut gamedatas = ecs.write_storage::<components::GameData>();
+            for gamedata in (&mut gamedatas).join() {
+                game_over = gamedata.game_over;
+                gamedata.respawn_timer = gamedata.respawn_timer.saturating_sub(1);
+                can_respawn = gamedata.respawn_timer == 0;
+            }
+        }
+
+        // The world is left as it is so the final score stays visible until a new game is started.
+        if game_over {
+            return;
         }
-    }
 
-    if must_reload_world {
-        ecs.delete_all();
-        load_world(ecs);
+        if can_respawn {
+            try_respawn_player(ecs);
+        }
     }
+
+    powerup::drop_powerups(ecs);
+    ufo::update_ufos(ecs, player_alive.then_some((current_player_position.x, current_player_position.y)));
     
     let mut must_create_asteroid = false;
     let mut number_asteroids: u32 = 0;
+    let mut level: u32 = 1;
 
     {
         let mut gamedatas = ecs.write_storage::<components::GameData>();
 
-        if crate::utils::is_key_pressed(&key_manager, "J"){
+        if input.just_pressed(Action::GodMode){
             for gamedata in (&mut gamedatas).join() {
                 gamedata.level += 9998;
                 number_asteroids = (gamedata.level / 3) + 1;
@@ -77,6 +276,13 @@ pub fn update(ecs: &mut World, key_manager: &mut HashMap<String, bool>){
             }
         }
 
+        for gamedata in (&mut gamedatas).join() {
+            if gamedata.score >= gamedata.next_extra_life {
+                gamedata.lives += 1;
+                gamedata.next_extra_life += EXTRA_LIFE_SCORE;
+            }
+        }
+
         let asteroids = ecs.read_storage::<crate::components::Asteroid>();
         if asteroids.join().count() < 1 {
             must_create_asteroid = true;
@@ -84,17 +290,26 @@ pub fn update(ecs: &mut World, key_manager: &mut HashMap<String, bool>){
             for gamedata in (&mut gamedatas).join() {
                 gamedata.level += 1;
                 number_asteroids = (gamedata.level / 3) + 1;
+                level = gamedata.level;
             }
         }
     }
 
+    let (arena_width, arena_height) = {
+        let arena = ecs.read_resource::<Arena>();
+        (arena.width as f64, arena.height as f64)
+    };
+
     if must_create_asteroid {
         let mut asteroid_count: u32 = 0;
         while asteroid_count < number_asteroids {
-            let mut rng = rand::thread_rng();
-            let next_x = rng.gen_range(50.0..(crate::GAME_WIDTH as f64 - 50.0));
-            let next_y = rng.gen_range(50.0..(crate::GAME_HEIGHT as f64 - 50.0));
-            let next_rot = rng.gen_range(0.0..360.0);
+            let (next_x, next_y, next_rot) = {
+                let mut game_rng = ecs.write_resource::<GameRng>();
+                let rng = &mut game_rng.rng;
+                (rng.gen_range(50.0..(arena_width - 50.0)),
+                 rng.gen_range(50.0..(arena_height - 50.0)),
+                 rng.gen_range(0.0..360.0))
+            };
 
             let diff_x = (current_player_position.x - next_x).abs();
             let diff_y = (current_player_position.y - next_y).abs();
@@ -109,34 +324,73 @@ pub fn update(ecs: &mut World, key_manager: &mut HashMap<String, bool>){
                 rot: next_rot
             };
 
-            create_asteroid(ecs, new_asteroid, 128);
+            // The kinds of asteroids change with the level, see data/asteroids.toml.
+            let (archetype, speed) = {
+                let archetypes = ecs.read_resource::<Archetypes>();
+                let mut game_rng = ecs.write_resource::<GameRng>();
+                let archetype = archetypes.pick(level, &mut game_rng.rng).clone();
+                let speed = game_rng.rng.gen_range(archetype.min_speed..=archetype.max_speed);
+                (archetype, speed)
+            };
+
+            spawn_asteroid(ecs, new_asteroid, 128, &archetype, speed);
         }
 
     }
 
     let mut player_pos = components::Position{x: 0.0, y: 0.0, rot: 0.0};
     let mut must_fire_missile = false;
+    let mut must_jump = false;
+    let mut bomb_at = None;
+    let mut weapon = 0;
+    let weapon_count = ecs.read_resource::<Weapons>().list.len();
 
     {
     let mut positions = ecs.write_storage::<crate::components::Position>();
     let mut players = ecs.write_storage::<crate::components::Player>();
     let mut renderables = ecs.write_storage::<crate::components::Renderable>();
+    let mut velocities = ecs.write_storage::<crate::components::Velocity>();
+
+    for(player, pos, renderable, velocity) in (&mut players, &mut positions, &mut renderables, &mut velocities).join() {
+        // Strength is 1 on a keyboard, analog sticks and triggers can apply less.
+        pos.rot += ROTATION_SPEED * input.strength(Action::RotateRight);
+        pos.rot -= ROTATION_SPEED * input.strength(Action::RotateLeft);
+
+        player.invulnerable = player.invulnerable.saturating_sub(1);
+        player.fire_cooldown = player.fire_cooldown.saturating_sub(1);
+        player.hyperspace_cooldown = player.hyperspace_cooldown.saturating_sub(1);
+
+        // The shield stays up while Shield is held and there's energy left. Once it has run dry it
+        // stays down until Shield is released or it has recharged a bit, instead of flickering back
+        // up with every point of energy regained.
+        let shield_held = input.is_held(Action::Shield) || input.just_pressed(Action::Shield);
+        if !shield_held || player.shield >= SHIELD_MIN_ENERGY {
+            player.shield_depleted = false;
+        }
+        player.shielded = shield_held && player.shield > 0 && !player.shield_depleted;
+        if player.shielded {
+            player.shield = player.shield.saturating_sub(SHIELD_DRAIN);
+            player.shield_depleted = player.shield == 0;
+        } else {
+            player.shield = (player.shield + 1).min(SHIELD_ENERGY);
+        }
 
-    for(player, pos, renderable) in (&mut players, &mut positions, &mut renderables).join() {
-        if crate::utils::is_key_pressed(&key_manager, "D"){
-            pos.rot += ROTATION_SPEED;
+        if input.just_pressed(Action::Hyperspace) && player.hyperspace_cooldown == 0 {
+            must_jump = true;
         }
-        
-        if crate::utils::is_key_pressed(&key_manager, "A"){
-            pos.rot -= ROTATION_SPEED;
+
+        if input.just_pressed(Action::Bomb) && player.bombs > 0 {
+            player.bombs -= 1;
+            bomb_at = Some((pos.x, pos.y));
         }
 
-        update_movement(pos, player);
-        if crate::utils::is_key_pressed(&key_manager, "W"){
+        update_movement(velocity, player);
+        let thrust = input.strength(Action::Thrust);
+        if thrust > 0.0 {
             let radians = pos.rot.to_radians();
 
-            let move_x = PLAYER_SPEED * radians.sin();
-            let move_y = PLAYER_SPEED * radians.cos();
+            let move_x = PLAYER_SPEED * thrust * radians.sin();
+            let move_y = -PLAYER_SPEED * thrust * radians.cos();
             let move_vec = Vector2D::<f64>::new(move_x, move_y);
 
             player.impulse += move_vec;
@@ -150,26 +404,17 @@ pub fn update(ecs: &mut World, key_manager: &mut HashMap<String, bool>){
             pos.rot += 360.0
         }
 
-        if pos.x > crate::GAME_WIDTH.into(){
-            pos.x -= crate::GAME_WIDTH as f64;
-        }
-        if pos.x < 0.0{
-            pos.x += crate::GAME_WIDTH as f64;
+        if input.just_pressed(Action::SwitchWeapon) {
+            player.weapon = (player.weapon + 1) % weapon_count;
         }
 
-        if pos.y > crate::GAME_HEIGHT.into(){
-            pos.y -= crate::GAME_HEIGHT as f64;
-        }
-        if pos.y < 0.0{
-            pos.y += crate::GAME_HEIGHT as f64;
-        }
-        
-        if utils::is_key_pressed(&key_manager, " ") {
-            utils::key_up(key_manager, " ".to_string());
+        // Holding Fire keeps shooting once the cooldown is over, a tap shorter than a tick still counts.
+        if (input.is_held(Action::Fire) || input.just_pressed(Action::Fire)) && player.fire_cooldown == 0 {
             must_fire_missile = true;
             player_pos.x = pos.x;
             player_pos.y = pos.y;
             player_pos.rot = pos.rot;
+            weapon = player.weapon;
         }
 
         // Update visual to reflect rotation
@@ -178,76 +423,236 @@ pub fn update(ecs: &mut World, key_manager: &mut HashMap<String, bool>){
     }
     }
 
+    if let Some((x, y)) = bomb_at {
+        bomb::spawn_shockwave(ecs, x, y);
+    }
+
+    if must_jump {
+        // A ship lost in hyperspace doesn't get to fire.
+        if !hyperspace(ecs) {
+            return;
+        }
+        // One that made it fires from where it came out.
+        let positions = ecs.read_storage::<components::Position>();
+        let players = ecs.read_storage::<components::Player>();
+        for (pos, _) in (&positions, &players).join() {
+            player_pos.x = pos.x;
+            player_pos.y = pos.y;
+        }
+    }
+
     if must_fire_missile {
-        fire_missile(ecs, player_pos);
+        if let Some(cooldown) = fire_missile(ecs, player_pos, weapon) {
+            let mut players = ecs.write_storage::<components::Player>();
+            for player in (&mut players).join() {
+                player.fire_cooldown = cooldown;
+            }
+        }
+    }
+
+}
+
+// Jumps the ship to the spot furthest from every asteroid and UFO out of a few random ones, and
+// stops it there. Now and then the jump goes wrong and the ship is lost, except in god mode.
+// Returns whether the ship made it.
+fn hyperspace(ecs: &mut World) -> bool {
+    let (arena_width, arena_height) = {
+        let arena = ecs.read_resource::<Arena>();
+        (arena.width as f64, arena.height as f64)
+    };
+    let god_mode = ecs.read_storage::<components::GameData>().join().any(|gamedata| gamedata.god_mode);
+
+    let (failed, spots) = {
+        let mut game_rng = ecs.write_resource::<GameRng>();
+        let rng = &mut game_rng.rng;
+        let failed = rng.gen_bool(HYPERSPACE_FAILURE_CHANCE);
+        let spots: Vec<Vector2D<f64>> = (0..HYPERSPACE_TRIES)
+            .map(|_| Vector2D::new(rng.gen_range(50.0..(arena_width - 50.0)), rng.gen_range(50.0..(arena_height - 50.0))))
+            .collect();
+        (failed, spots)
+    };
+
+    if failed && !god_mode {
+        {
+            let players = ecs.read_storage::<components::Player>();
+            let entities = ecs.entities();
+            for (_, entity) in (&players, &entities).join() {
+                entities.delete(entity).ok();
+            }
+        }
+        let mut gamedatas = ecs.write_storage::<components::GameData>();
+        for gamedata in (&mut gamedatas).join() {
+            gamedata.lose_ships(1);
+        }
+        return false;
     }
 
+    let destination = {
+        let positions = ecs.read_storage::<components::Position>();
+        let rends = ecs.read_storage::<components::Renderable>();
+        let asteroids = ecs.read_storage::<components::Asteroid>();
+        let ufos = ecs.read_storage::<components::Ufo>();
+        let entities = ecs.entities();
+
+        // Room around a spot, up to the edge of the closest asteroid or UFO.
+        let clearance = |spot: &Vector2D<f64>| -> f64 {
+            (&positions, &rends, &entities).join()
+                .filter(|(_, _, entity)| asteroids.contains(*entity) || ufos.contains(*entity))
+                .map(|(pos, rend, _)| (Vector2D::new(pos.x, pos.y) - *spot).length() - rend.o_w as f64 / 2.0)
+                .fold(f64::MAX, f64::min)
+        };
+        spots.into_iter()
+            .map(|spot| (clearance(&spot), spot))
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, spot)| spot)
+    };
+
+    let Some(destination) = destination else {
+        return true;
+    };
+
+    let mut positions = ecs.write_storage::<components::Position>();
+    let mut velocities = ecs.write_storage::<components::Velocity>();
+    let mut players = ecs.write_storage::<components::Player>();
+    for (pos, velocity, player) in (&mut positions, &mut velocities, &mut players).join() {
+        pos.x = destination.x;
+        pos.y = destination.y;
+        velocity.0 = Vector2D::new(0.0, 0.0);
+        player.impulse = Vector2D::new(0.0, 0.0);
+        player.hyperspace_cooldown = HYPERSPACE_COOLDOWN;
+    }
+    true
 }
 
 const MAX_SPEED: f64 = 3.5;
 const FRICTION: f64 = 0.99;
 
-pub fn update_movement(pos: &mut crate::components::Position, player: &mut crate::components::Player){
-    player.cur_speed *= FRICTION;
+// Applies friction and last tick's thrust to the ship's velocity, the Mover system moves it.
+pub fn update_movement(velocity: &mut crate::components::Velocity, player: &mut crate::components::Player){
+    velocity.0 *= FRICTION;
 
-    player.cur_speed += player.impulse;
-    if player.cur_speed.length() > MAX_SPEED {
-        player.cur_speed = player.cur_speed.normalise();
-        player.cur_speed = player.cur_speed * MAX_SPEED;
+    velocity.0 += player.impulse;
+    if velocity.0.length() > MAX_SPEED {
+        velocity.0 = velocity.0.normalise();
+        velocity.0 *= MAX_SPEED;
     }
 
-    pos.x += player.cur_speed.x;
-    pos.y -= player.cur_speed.y;
-
     player.impulse = Vector2D::new(0.0, 0.0);
 }
 
-const MAX_MISSILES: usize = 5;
-
-fn fire_missile(ecs: &mut World, position: components::Position){
+// Fires the weapon at the given index unless too many of its shots are in flight already.
+// Returns the weapon's cooldown if it fired.
+fn fire_missile(ecs: &mut World, position: components::Position, index: usize) -> Option<u32> {
+    let weapon = ecs.read_resource::<Weapons>().get(index).clone();
+    let (rapid_fire, triple_shot) = ecs.read_storage::<components::ActiveEffects>().join().next()
+        .map_or((false, false), |effects| (effects.rapid_fire > 0, effects.triple_shot > 0));
+    let factor = if rapid_fire { powerup::RAPID_FIRE_FACTOR } else { 1 };
     {
         let missiles = ecs.read_storage::<components::Missile>();
-        if missiles.count() > MAX_MISSILES - 1 {
-            return;
+        let active = missiles.join().filter(|missile| missile.weapon == weapon.name).count();
+        if weapon.max_active.is_some_and(|max_active| active >= max_active * factor as usize) {
+            return None;
         }
     }
 
+    // Triple shot adds a shot on either side of every shot.
+    let extra_angles: &[f64] = if triple_shot { &[-powerup::TRIPLE_SHOT_ANGLE, 0.0, powerup::TRIPLE_SHOT_ANGLE] } else { &[0.0] };
+
+    // Several shots are fanned out evenly, the outer ones spread / 2 degrees off the heading.
+    for shot in 0..weapon.shots {
+        let offset = if weapon.shots > 1 {
+            weapon.spread * (shot as f64 / (weapon.shots - 1) as f64 - 0.5)
+        } else {
+            0.0
+        };
+        for extra_angle in extra_angles {
+            spawn_missile(ecs, components::Position{rot: position.rot + offset + extra_angle, ..position}, &weapon);
+        }
+    }
+    Some((weapon.cooldown / factor).max(1))
+}
 
-    ecs.create_entity()
+// Creates a shot of the weapon flying in the direction of position.rot, ignoring the max_active limit.
+pub fn spawn_missile(ecs: &mut World, position: components::Position, weapon: &Weapon) -> Entity {
+    let radians = position.rot.to_radians();
+    let velocity = Vector2D::new(weapon.speed * radians.sin(), -weapon.speed * radians.cos());
+    let edges = ecs.read_resource::<Rules>().missile_edges;
+    let fallback = Shape::Box{half_width: weapon.width as f64 / 2.0, half_height: weapon.height as f64 / 2.0};
+    let collider = collider_for(ecs, &weapon.texture, weapon.width, weapon.height, fallback);
+
+    let rot = position.rot;
+    let mut builder = ecs.create_entity()
+        .with(components::PreviousPosition{x: position.x, y: position.y})
         .with(position)
         .with(components::Renderable{
-            tex_name: String::from("img/bullet.png"),
-            i_w: 8,
-            i_h: 32,
-            o_w: 8,
-            o_h: 32,
+            tex_name: weapon.texture.clone(),
+            i_w: weapon.texture_width,
+            i_h: weapon.texture_height,
+            o_w: weapon.width,
+            o_h: weapon.height,
             frame: 0,
             total_frames: 1,
-            rot: 0.0
+            rot
         })
+        .with(components::Velocity(velocity))
+        .with(edges)
+        .with(collider)
+        .with(components::Lifetime{ticks: weapon.lifetime, distance: weapon.range})
         .with(components::Missile{
-            speed: 5.0
-        })
-        .build();
+            weapon: weapon.name.clone(),
+            hostile: false,
+            damage: weapon.damage,
+            piercing: weapon.piercing,
+            pierced: Vec::new()
+        });
+
+    if let Some(turn_rate) = weapon.turn_rate {
+        builder = builder.with(components::Homing{turn_rate});
+    }
+    builder.build()
+}
 
+// Creates an asteroid of the first kind at its slowest speed, flying in the direction of position.rot.
+pub fn create_asteroid(ecs: &mut World, position: components::Position, asteroid_size: u32) -> Entity {
+    let archetype = ecs.read_resource::<Archetypes>().first().clone();
+    let speed = archetype.min_speed;
+    spawn_asteroid(ecs, position, asteroid_size, &archetype, speed)
 }
 
-pub fn create_asteroid(ecs: &mut World, position: components::Position, asteroid_size: u32){
-    ecs.create_entity()
+// Creates an asteroid of the given kind flying in the direction of position.rot.
+pub fn spawn_asteroid(ecs: &mut World, position: components::Position, asteroid_size: u32, archetype: &Archetype, speed: f64) -> Entity {
+    let radians = position.rot.to_radians();
+    let velocity = Vector2D::new(speed * radians.sin(), -speed * radians.cos());
+    let edges = ecs.read_resource::<Rules>().asteroid_edges;
+    let health = archetype.health(asteroid_size);
+
+    let mut builder = ecs.create_entity()
+        .with(components::PreviousPosition{x: position.x, y: position.y})
         .with(position)
         .with(components::Renderable{
-            tex_name: String::from("img/asteroid.png"),
-            i_w: 256,
-            i_h: 256,
+            tex_name: archetype.texture.clone(),
+            i_w: archetype.texture_width,
+            i_h: archetype.texture_height,
             o_w: asteroid_size,
             o_h: asteroid_size,
             frame: 0,
             total_frames: 1,
             rot: 0.0
         })
+        .with(components::Velocity(velocity))
+        .with(edges)
+        .with(components::Collider{shape: Shape::Circle{radius: asteroid_size as f64 / 2.0}})
+        .with(components::Health{current: health, max: health})
         .with(components::Asteroid{
-            speed: 2.5,
-            rot_speed: 0.5
-        })
-        .build();
+            rot_speed: archetype.rot_speed,
+            kind: archetype.name.clone()
+        });
+
+    if let Some(explosive) = &archetype.explosive {
+        builder = builder.with(explosive.clone());
+    }
+    if let Some(chilling) = &archetype.chilling {
+        builder = builder.with(chilling.clone());
+    }
+    builder.build()
 }
\ No newline at end of file
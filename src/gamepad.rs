@@ -0,0 +1,124 @@
+use std::collections::{HashMap, HashSet};
+
+use sdl2::GameControllerSubsystem;
+use sdl2::controller::GameController;
+use sdl2::event::Event;
+
+use asteroid_game::input::{Action, InputMap, InputState};
+
+// Stick positions closer to the center than this are treated as centered.
+const DEADZONE: f64 = 0.2;
+// How far an axis has to be pushed to press a digital action such as Fire.
+const DIGITAL_THRESHOLD: f64 = 0.5;
+
+// Keeps every connected game controller open and turns their events into actions.
+pub struct Gamepads {
+    subsystem: GameControllerSubsystem,
+    // Open controllers by joystick instance id, closed again when dropped.
+    controllers: HashMap<u32, GameController>,
+    // Buttons and pushed axes holding down a digital action, by their binding name. An action is
+    // only released once none of them holds it anymore.
+    holding: HashSet<(String, Action)>
+}
+
+impl Gamepads {
+    pub fn new(subsystem: GameControllerSubsystem) -> Self {
+        Gamepads {
+            subsystem,
+            controllers: HashMap::new(),
+            holding: HashSet::new()
+        }
+    }
+
+    // Returns true if the event pressed Pause, which the front end handles itself. Other presses
+    // only count while playing, so nothing pressed on a menu goes off once the game resumes.
+    pub fn handle_event(&mut self, event: &Event, input_map: &InputMap, input: &mut InputState, playing: bool) -> bool {
+        match event {
+            // SDL also sends this for every controller that is already connected on startup.
+            Event::ControllerDeviceAdded { which, .. } => {
+                match self.subsystem.open(*which) {
+                    Ok(controller) => {
+                        self.controllers.insert(controller.instance_id(), controller);
+                    },
+                    Err(e) => eprintln!("Could not open game controller {}: {}", which, e)
+                }
+            },
+            Event::ControllerDeviceRemoved { which, .. } => {
+                self.controllers.remove(which);
+                // Don't leave the ship thrusting because the controller was unplugged mid press.
+                self.holding.clear();
+                input.release_all();
+            },
+            Event::ControllerButtonDown { button, .. } => {
+                let name = button.string();
+                for action in input_map.gamepad_actions(&name) {
+                    if action == Action::Pause {
+                        return true;
+                    }
+                    if playing {
+                        self.hold(&name, action, input);
+                    }
+                }
+            },
+            Event::ControllerButtonUp { button, .. } => {
+                let name = button.string();
+                for action in input_map.gamepad_actions(&name) {
+                    self.let_go(&name, action, input);
+                }
+            },
+            Event::ControllerAxisMotion { axis, value, .. } => {
+                let name = axis.string();
+                let position = *value as f64 / i16::MAX as f64;
+                let positive = apply_deadzone(position.max(0.0));
+                let negative = apply_deadzone((-position).max(0.0));
+
+                let bindings = [
+                    (name.clone(), positive),
+                    (format!("+{}", name), positive),
+                    (format!("-{}", name), negative)
+                ];
+
+                for (binding, strength) in bindings {
+                    for action in input_map.gamepad_actions(&binding) {
+                        if action.is_analog() {
+                            input.set_axis(action, strength);
+                        } else if strength > DIGITAL_THRESHOLD {
+                            if playing {
+                                self.hold(&binding, action, input);
+                            }
+                        } else {
+                            // Only lets go of what this axis pressed, the same action may be held
+                            // with a button at the same time.
+                            self.let_go(&binding, action, input);
+                        }
+                    }
+                }
+            },
+            _ => {}
+        }
+        false
+    }
+
+    fn hold(&mut self, binding: &str, action: Action, input: &mut InputState) {
+        self.holding.insert((binding.to_string(), action));
+        input.press(action);
+    }
+
+    fn let_go(&mut self, binding: &str, action: Action, input: &mut InputState) {
+        if !self.holding.remove(&(binding.to_string(), action)) {
+            return;
+        }
+        if !self.holding.iter().any(|(_, held)| *held == action) {
+            input.release(action);
+        }
+    }
+}
+
+// Rescales so the stick goes smoothly from 0 at the edge of the deadzone to 1 at full tilt.
+fn apply_deadzone(strength: f64) -> f64 {
+    if strength < DEADZONE {
+        0.0
+    } else {
+        ((strength - DEADZONE) / (1.0 - DEADZONE)).min(1.0)
+    }
+}
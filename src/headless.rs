@@ -0,0 +1,52 @@
+use specs::{WorldExt, Join};
+
+use crate::components;
+use crate::game;
+use crate::input::InputState;
+use crate::replay::Playback;
+use crate::resources::{Arena, Hulls, Rules};
+use crate::weapons::Weapons;
+use crate::archetypes::Archetypes;
+
+// Runs the simulation for the given number of fixed steps without creating a window,
+// then prints the final game data. A replay with a recorded length stops it early once it
+// has been played back.
+pub fn run(ticks: u32, seed: u64, rules: Rules, hulls: Hulls, weapons: Weapons, archetypes: Archetypes, mut playback: Option<Playback>) -> Result<(), String> {
+    let mut ecs = game::create_world(Arena::default(), seed);
+    ecs.insert(rules);
+    ecs.insert(hulls);
+    ecs.insert(weapons);
+    ecs.insert(archetypes);
+    let mut dispatcher = game::build_dispatcher();
+    // Started like a game from the title screen of the windowed front end, so replays recorded
+    // there begin with the exact same world.
+    game::load_world(&mut ecs);
+    game::new_game(&mut ecs);
+
+    let mut input = InputState::default();
+    let mut simulated: u32 = 0;
+
+    while simulated < ticks {
+        if let Some(playback) = &mut playback {
+            if playback.finished() {
+                break;
+            }
+            playback.apply(&mut input);
+        }
+
+        game::step(&mut ecs, &mut dispatcher, &mut input);
+        simulated += 1;
+    }
+
+    let gamedatas = ecs.read_storage::<components::GameData>();
+    for gamedata in gamedatas.join() {
+        println!("seed: {}", seed);
+        println!("ticks: {}", simulated);
+        println!("score: {}", gamedata.score);
+        println!("level: {}", gamedata.level);
+        println!("lives: {}", gamedata.lives);
+        println!("game over: {}", gamedata.game_over);
+    }
+
+    Ok(())
+}
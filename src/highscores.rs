@@ -0,0 +1,125 @@
+use std::cmp::Reverse;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+pub const MAX_ENTRIES: usize = 10;
+pub const MAX_NAME_LENGTH: usize = 3;
+// Saved for a player who confirms without typing anything, e.g. with only a controller.
+pub const DEFAULT_NAME: &str = "???";
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HighScore {
+    pub name: String,
+    pub score: u32,
+    pub level: u32,
+    // Day the score was set, as YYYY-MM-DD
+    pub date: String,
+    // Seed of the game, so the run can be played again with --seed
+    pub seed: u64
+}
+
+// The best scores, highest first. Stored as TOML:
+//
+//     [[scores]]
+//     name = "ABC"
+//     score = 1230
+//     level = 4
+//     date = "2024-05-01"
+//     seed = 1234
+#[derive(Serialize, Deserialize, Default)]
+pub struct HighScoreTable {
+    #[serde(default)]
+    scores: Vec<HighScore>
+}
+
+impl HighScoreTable {
+    // Never fails: a missing file gives an empty table, a corrupt one is moved aside to
+    // <file>.bak so it isn't overwritten by the next save, and an empty table is used instead.
+    pub fn load(path: &Path) -> HighScoreTable {
+        if !path.exists() {
+            return HighScoreTable::default();
+        }
+
+        let parsed = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| toml::from_str::<HighScoreTable>(&text).map_err(|e| e.to_string()));
+
+        match parsed {
+            Ok(mut table) => {
+                // The file may have been edited by hand, so don't trust its order or length.
+                table.scores.sort_by_key(|entry| Reverse(entry.score));
+                table.scores.truncate(MAX_ENTRIES);
+                table
+            },
+            Err(e) => {
+                let backup = path.with_extension("toml.bak");
+                eprintln!("Could not read high scores from {}: {}", path.display(), e);
+                if let Err(e) = fs::rename(path, &backup) {
+                    eprintln!("Could not move {} to {}: {}", path.display(), backup.display(), e);
+                }
+                HighScoreTable::default()
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = toml::to_string(self).map_err(|e| e.to_string())?;
+        fs::write(path, text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn scores(&self) -> &[HighScore] {
+        &self.scores
+    }
+
+    // Whether a score is good enough to get a place in the table.
+    pub fn qualifies(&self, score: u32) -> bool {
+        if score == 0 {
+            return false;
+        }
+        self.scores.len() < MAX_ENTRIES || self.scores.last().is_none_or(|lowest| score > lowest.score)
+    }
+
+    // Inserts a score at its place, dropping the lowest one if the table is full.
+    // Returns the place it got, or None if it didn't qualify.
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        if !self.qualifies(entry.score) {
+            return None;
+        }
+
+        // Ties go below the existing entries, the earlier score keeps its place.
+        let place = self.scores.iter().position(|existing| entry.score > existing.score).unwrap_or(self.scores.len());
+        self.scores.insert(place, entry);
+        self.scores.truncate(MAX_ENTRIES);
+        Some(place)
+    }
+}
+
+// The initials to save, DEFAULT_NAME if none were entered.
+pub fn name_or_default(name: &str) -> &str {
+    if name.is_empty() { DEFAULT_NAME } else { name }
+}
+
+// Today's date in UTC as YYYY-MM-DD.
+pub fn today() -> String {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs());
+    let (year, month, day) = civil_from_days((seconds / 86400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// Converts days since 1970-01-01 to a (year, month, day) date in the proleptic Gregorian calendar.
+// Howard Hinnant's algorithm, see http://howardhinnant.github.io/date_algorithms.html
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
@@ -0,0 +1,344 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+// Everything the player can do. The simulation only ever sees actions, never physical keys.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub enum Action {
+    Thrust,
+    RotateLeft,
+    RotateRight,
+    Fire,
+    SwitchWeapon,
+    Hyperspace,
+    Shield,
+    Bomb,
+    Pause,
+    GodMode
+}
+
+impl Action {
+    pub const ALL: [Action; 10] = [
+        Action::Thrust,
+        Action::RotateLeft,
+        Action::RotateRight,
+        Action::Fire,
+        Action::SwitchWeapon,
+        Action::Hyperspace,
+        Action::Shield,
+        Action::Bomb,
+        Action::Pause,
+        Action::GodMode
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Thrust => "Thrust",
+            Action::RotateLeft => "RotateLeft",
+            Action::RotateRight => "RotateRight",
+            Action::Fire => "Fire",
+            Action::SwitchWeapon => "SwitchWeapon",
+            Action::Hyperspace => "Hyperspace",
+            Action::Shield => "Shield",
+            Action::Bomb => "Bomb",
+            Action::Pause => "Pause",
+            Action::GodMode => "GodMode"
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.iter().find(|action| action.name() == name).copied()
+    }
+
+    // Analog actions take a strength from 0 to 1, e.g. from a trigger, instead of only on or off.
+    pub fn is_analog(&self) -> bool {
+        matches!(self, Action::Thrust | Action::RotateLeft | Action::RotateRight)
+    }
+}
+
+// A single change to the input state, in the order it happened.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum InputEvent {
+    Press(Action),
+    Release(Action),
+    Axis(Action, f64)
+}
+
+// Maps physical inputs to actions. Keys are identified by their SDL key name ("W", "Space",
+// "Left Shift"...), one key per action. Game controller inputs use the SDL button and axis names
+// ("a", "dpleft", "righttrigger"...), an axis can be prefixed with + or - to only use one direction.
+// An action can have several controller inputs, e.g. both the stick and the d-pad.
+//
+// Bindings can be loaded from a TOML file, actions missing from the file keep their default binding:
+//
+//     [keyboard]
+//     Thrust = "Z"
+//     RotateLeft = "Q"
+//
+//     [gamepad]
+//     Thrust = "righttrigger"
+//     RotateLeft = ["-leftx", "dpleft"]
+pub struct InputMap {
+    keys: HashMap<Action, String>,
+    gamepad: HashMap<Action, Vec<String>>
+}
+
+impl InputMap {
+    pub fn action_for(&self, key: &str) -> Option<Action> {
+        self.keys.iter()
+            .find(|(_, bound)| bound.as_str() == key)
+            .map(|(action, _)| *action)
+    }
+
+    pub fn key_for(&self, action: Action) -> Option<&str> {
+        self.keys.get(&action).map(|key| key.as_str())
+    }
+
+    // All actions bound to a controller button or axis direction.
+    pub fn gamepad_actions(&self, input: &str) -> Vec<Action> {
+        Action::ALL.iter()
+            .filter(|action| self.gamepad_inputs_for(**action).iter().any(|bound| bound == input))
+            .copied()
+            .collect()
+    }
+
+    pub fn gamepad_inputs_for(&self, action: Action) -> &[String] {
+        self.gamepad.get(&action).map_or(&[], |inputs| inputs.as_slice())
+    }
+
+    // Binds a key to an action, refusing keys that are already used by another action.
+    pub fn bind(&mut self, action: Action, key: &str) -> Result<(), String> {
+        match self.action_for(key) {
+            Some(bound) if bound != action => {
+                Err(format!("\"{}\" is already bound to {}", key, bound.name()))
+            },
+            _ => {
+                self.keys.insert(action, key.to_string());
+                Ok(())
+            }
+        }
+    }
+
+    // The key names are checked with known_key, the front end knows which ones SDL has.
+    pub fn parse(text: &str, known_key: impl Fn(&str) -> bool) -> Result<InputMap, String> {
+        let table = text.parse::<toml::Table>().map_err(|e| e.to_string())?;
+        let mut input_map = InputMap::default();
+
+        // Validate against the whole file rather than binding one by one, so swapping two keys works.
+        for (name, value) in section(&table, "keyboard")?.into_iter().flatten() {
+            let action = Action::from_name(name)
+                .ok_or(format!("unknown action \"{}\" in [keyboard]", name))?;
+            let key = value.as_str()
+                .ok_or(format!("key for {} must be a string", name))?;
+            if !known_key(key) {
+                return Err(format!("unknown key \"{}\" for {}", key, name));
+            }
+            input_map.keys.insert(action, key.to_string());
+        }
+
+        for (name, value) in section(&table, "gamepad")?.into_iter().flatten() {
+            let action = Action::from_name(name)
+                .ok_or(format!("unknown action \"{}\" in [gamepad]", name))?;
+            let inputs = match value {
+                toml::Value::String(input) => vec![input.clone()],
+                toml::Value::Array(inputs) => inputs.iter()
+                    .map(|input| input.as_str().map(|input| input.to_string()))
+                    .collect::<Option<Vec<String>>>()
+                    .ok_or(format!("controller inputs for {} must be strings", name))?,
+                _ => return Err(format!("controller input for {} must be a string or a list of strings", name))
+            };
+            input_map.gamepad.insert(action, inputs);
+        }
+
+        input_map.validate()?;
+        Ok(input_map)
+    }
+
+    // Returns an error naming both actions if a key is bound more than once.
+    pub fn validate(&self) -> Result<(), String> {
+        for (i, first) in Action::ALL.iter().enumerate() {
+            for second in &Action::ALL[i + 1..] {
+                if let (Some(first_key), Some(second_key)) = (self.key_for(*first), self.key_for(*second)) {
+                    if first_key == second_key {
+                        return Err(format!("\"{}\" is bound to both {} and {}", first_key, first.name(), second.name()));
+                    }
+                }
+
+                for input in self.gamepad_inputs_for(*first) {
+                    if self.gamepad_inputs_for(*second).contains(input) {
+                        return Err(format!("controller input \"{}\" is bound to both {} and {}", input, first.name(), second.name()));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn to_toml(&self) -> String {
+        let mut text = "[keyboard]\n".to_string();
+        for action in Action::ALL {
+            if let Some(key) = self.key_for(action) {
+                text += &format!("{} = {}\n", action.name(), toml::Value::String(key.to_string()));
+            }
+        }
+
+        text += "\n[gamepad]\n";
+        for action in Action::ALL {
+            let inputs = self.gamepad_inputs_for(action);
+            if !inputs.is_empty() {
+                let list: toml::Value = inputs.iter().map(|input| toml::Value::String(input.clone())).collect::<Vec<_>>().into();
+                text += &format!("{} = {}\n", action.name(), list);
+            }
+        }
+        text
+    }
+
+    // A missing file is not an error, the default bindings are used instead.
+    pub fn load(path: &Path, known_key: impl Fn(&str) -> bool) -> Result<InputMap, String> {
+        if !path.exists() {
+            return Ok(InputMap::default());
+        }
+
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        InputMap::parse(&text, known_key).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.to_toml()).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        let keys = [
+            (Action::Thrust, "W"),
+            (Action::RotateLeft, "A"),
+            (Action::RotateRight, "D"),
+            (Action::Fire, "Space"),
+            (Action::SwitchWeapon, "E"),
+            (Action::Hyperspace, "S"),
+            (Action::Shield, "Left Shift"),
+            (Action::Bomb, "B"),
+            (Action::Pause, "P"),
+            (Action::GodMode, "J")
+        ];
+
+        let gamepad: [(Action, &[&str]); 9] = [
+            (Action::Thrust, &["righttrigger", "dpup"]),
+            (Action::RotateLeft, &["-leftx", "dpleft"]),
+            (Action::RotateRight, &["+leftx", "dpright"]),
+            (Action::Fire, &["a"]),
+            (Action::SwitchWeapon, &["y"]),
+            (Action::Hyperspace, &["b"]),
+            (Action::Shield, &["x"]),
+            (Action::Bomb, &["leftshoulder"]),
+            (Action::Pause, &["start"])
+        ];
+
+        InputMap {
+            keys: keys.iter().map(|(action, key)| (*action, key.to_string())).collect(),
+            gamepad: gamepad.iter()
+                .map(|(action, inputs)| (*action, inputs.iter().map(|input| input.to_string()).collect()))
+                .collect()
+        }
+    }
+}
+
+// An optional [section] of the controls file.
+fn section<'t>(table: &'t toml::Table, name: &str) -> Result<Option<&'t toml::Table>, String> {
+    match table.get(name) {
+        Some(toml::Value::Table(section)) => Ok(Some(section)),
+        Some(_) => Err(format!("[{}] must be a table", name)),
+        None => Ok(None)
+    }
+}
+
+// Per tick action state. Presses and releases are latched until end_tick, so a key that is
+// tapped between two fixed steps still registers.
+#[derive(Default)]
+pub struct InputState {
+    held: HashSet<Action>,
+    pressed: HashSet<Action>,
+    released: HashSet<Action>,
+    // Latest value of analog inputs, from 0 to 1.
+    axes: HashMap<Action, f64>,
+    // Every state change since the last tick, in order. Used for recording replays.
+    events: Vec<InputEvent>
+}
+
+impl InputState {
+    pub fn apply(&mut self, event: InputEvent) {
+        match event {
+            InputEvent::Press(action) => self.press(action),
+            InputEvent::Release(action) => self.release(action),
+            InputEvent::Axis(action, value) => self.set_axis(action, value)
+        }
+    }
+
+    pub fn press(&mut self, action: Action) {
+        if self.held.insert(action) {
+            self.pressed.insert(action);
+            self.events.push(InputEvent::Press(action));
+        }
+    }
+
+    pub fn release(&mut self, action: Action) {
+        if self.held.remove(&action) {
+            self.released.insert(action);
+            self.events.push(InputEvent::Release(action));
+        }
+    }
+
+    pub fn set_axis(&mut self, action: Action, value: f64) {
+        let value = value.clamp(0.0, 1.0);
+        if self.axes.get(&action).copied().unwrap_or(0.0) != value {
+            self.axes.insert(action, value);
+            self.events.push(InputEvent::Axis(action, value));
+        }
+    }
+
+    // Releases every held action and centers every axis, e.g. when a menu takes over the keyboard.
+    pub fn release_all(&mut self) {
+        let held: Vec<Action> = self.held.iter().copied().collect();
+        for action in held {
+            self.release(action);
+        }
+
+        let axes: Vec<Action> = self.axes.keys().copied().collect();
+        for action in axes {
+            self.set_axis(action, 0.0);
+        }
+    }
+
+    pub fn is_held(&self, action: Action) -> bool {
+        self.held.contains(&action)
+    }
+
+    // How strongly an action is applied, 1 when held down on a digital input.
+    pub fn strength(&self, action: Action) -> f64 {
+        if self.is_held(action) {
+            1.0
+        } else {
+            self.axes.get(&action).copied().unwrap_or(0.0)
+        }
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_released(&self, action: Action) -> bool {
+        self.released.contains(&action)
+    }
+
+    pub fn events(&self) -> &[InputEvent] {
+        &self.events
+    }
+
+    // Called after every simulated tick.
+    pub fn end_tick(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+        self.events.clear();
+    }
+}
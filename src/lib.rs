@@ -0,0 +1,21 @@
+// Game simulation, independent of SDL. The binary in main.rs is only a front end
+// that feeds input into game::update and draws the resulting World.
+pub mod components;
+pub mod movement;
+pub mod input;
+pub mod resources;
+pub mod state;
+pub mod game;
+pub mod asteroid;
+pub mod missile;
+pub mod spatial;
+pub mod collision;
+pub mod replay;
+pub mod highscores;
+pub mod headless;
+pub mod weapons;
+pub mod archetypes;
+pub mod ufo;
+pub mod powerup;
+pub mod bomb;
+pub mod score;
@@ -1,34 +1,44 @@
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
-use sdl2::render::{WindowCanvas, TextureCreator, Texture};
+use sdl2::render::{WindowCanvas, TextureCreator, Texture, BlendMode};
 use sdl2::video::WindowContext;
+use sdl2::ttf::Font;
 use sdl2::pixels::Color;
+use sdl2::image::LoadTexture;
 use sdl2::rect::{Rect, Point};
 
-use specs::{World, WorldExt, Join, DispatcherBuilder, System};
+use specs::{World, WorldExt, Join, LendJoin};
+
+use asteroid_game::{bomb, collision, components, game, headless, powerup, score};
+use asteroid_game::components::BoundaryBehavior;
+use asteroid_game::input::{Action, InputMap, InputState};
+use asteroid_game::replay::{Replay, Recorder, Playback};
+use asteroid_game::resources::{Arena, GameRng, Hulls, Rules, ARENA_WIDTH, ARENA_HEIGHT};
+use asteroid_game::state::{GameState, StateMachine};
+use asteroid_game::highscores::{self, HighScore, HighScoreTable, MAX_NAME_LENGTH};
+use asteroid_game::weapons::Weapons;
+use asteroid_game::archetypes::Archetypes;
 
 use std::time::Instant;
-use std::path::Path;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::vec::Vec;
 
-pub mod texture_manager;
-pub mod utils;
-pub mod components;
-pub mod game;
-pub mod asteroid;
-pub mod missile;
-
-const GAME_WIDTH: u32 = 1280;
-const GAME_HEIGHT: u32 = 640;
+mod texture_manager;
+mod ui;
+mod controls_screen;
+mod gamepad;
+mod menus;
 
-pub struct UIElement<'a>{
-    texture : Texture<'a>,
-    position : Rect
-}
+use ui::UIElement;
+use controls_screen::ControlsScreen;
+use gamepad::Gamepads;
 
+// The window shows the whole arena, so it's the size headless runs simulate as well.
+const GAME_WIDTH: u32 = ARENA_WIDTH;
+const GAME_HEIGHT: u32 = ARENA_HEIGHT;
 
-fn render(canvas: &mut WindowCanvas, texture_manager: &mut texture_manager::TextureManager<WindowContext>, ecs: &World, current_score : u32, ui_elements : &Vec<UIElement>) -> Result<(), String> {
+fn render(canvas: &mut WindowCanvas, texture_manager: &mut texture_manager::TextureManager<WindowContext>, flash_textures: &HashMap<String, Texture>, ecs: &World, ui_elements : &Vec<UIElement>, popups : &Vec<UIElement>, overlay : Option<&Vec<UIElement>>) -> Result<(), String> {
 
     let color = Color::RGBA(0, 10, 100, 255);
 
@@ -37,8 +47,26 @@ fn render(canvas: &mut WindowCanvas, texture_manager: &mut texture_manager::Text
 
     let positions = ecs.read_storage::<components::Position>();
     let renderables = ecs.read_storage::<components::Renderable>();
+    let players = ecs.read_storage::<components::Player>();
+    let flashes = ecs.read_storage::<components::HitFlash>();
+    let powerups = ecs.read_storage::<components::PowerUp>();
+    let lifetimes = ecs.read_storage::<components::Lifetime>();
+
+    for(renderable, pos, player, flash, powerup, lifetime) in (&renderables, &positions, (&players).maybe(), (&flashes).maybe(), (&powerups).maybe(), (&lifetimes).maybe()).join(){
+        // Invulnerable ships blink.
+        if let Some(player) = player {
+            if player.invulnerable > 0 && (player.invulnerable / 8) % 2 == 0 {
+                continue;
+            }
+        }
+
+        // So do power-ups about to disappear.
+        if let (Some(_), Some(components::Lifetime{ticks: Some(ticks), ..})) = (powerup, lifetime) {
+            if *ticks < powerup::BLINK_TICKS && (ticks / 8) % 2 == 0 {
+                continue;
+            }
+        }
 
-    for(renderable, pos) in (&renderables, &positions).join(){
         let src = Rect::new(0, 0, renderable.i_w, renderable.i_h);
         let x: i32 = pos.x as i32;
         let y: i32 = pos.y as i32;
@@ -46,8 +74,13 @@ fn render(canvas: &mut WindowCanvas, texture_manager: &mut texture_manager::Text
 
         let center = Point::new((renderable.o_w/2) as i32, (renderable.o_h/2) as i32);
         let texture = texture_manager.load(&renderable.tex_name)?;
+        // Just hit, drawn with the tinted copy if there is one.
+        let texture = match (flash, flash_textures.get(&renderable.tex_name)) {
+            (Some(flash), Some(flash_texture)) if flash.ticks > 0 => flash_texture,
+            _ => texture.as_ref()
+        };
         canvas.copy_ex(
-            &texture,
+            texture,
             src,
             dest,
             renderable.rot,
@@ -55,19 +88,200 @@ fn render(canvas: &mut WindowCanvas, texture_manager: &mut texture_manager::Text
             false,
             false
         )?;
+
+        if player.is_some_and(|player| player.shielded) {
+            let size = (game::SHIELD_RADIUS * 2.0) as u32;
+            let shield_dest = Rect::new(x - (size/2) as i32, y - (size/2) as i32, size, size);
+            canvas.copy(texture_manager.load("img/shield.png")?.as_ref(), None, Some(shield_dest))?;
+        }
+    }
+
+    for popup in popups {
+        canvas.copy(&popup.texture, None, Some(popup.position))?;
     }
 
     for ui_element in ui_elements  {
         canvas.copy(&ui_element.texture, None, Some(ui_element.position))?;
     }
 
+    // The shield's energy changes every tick, so its meter is drawn instead of being a text element.
+    if let Some(player) = players.join().next() {
+        let meter = Rect::new(GAME_WIDTH as i32 - 160, 92, 150, 16);
+        canvas.set_draw_color(Color::RGBA(15, 180, 75, 255));
+        canvas.draw_rect(meter)?;
+        let filled = player.shield * (meter.width() - 4) / game::SHIELD_ENERGY;
+        if filled > 0 {
+            canvas.fill_rect(Rect::new(meter.x() + 2, meter.y() + 2, filled, meter.height() - 4))?;
+        }
+    }
+
+    // Menus are drawn on top of the darkened game.
+    if let Some(overlay) = overlay {
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(Color::RGBA(0, 0, 0, 180));
+        canvas.fill_rect(None)?;
+        canvas.set_blend_mode(BlendMode::None);
+
+        for ui_element in overlay {
+            canvas.copy(&ui_element.texture, None, Some(ui_element.position))?;
+        }
+    }
+
     canvas.present();
     Ok(())
 }
 
 struct State { ecs: World }
 
+// Key bindings and high scores are stored next to the executable.
+fn data_path(file_name: &str) -> PathBuf {
+    std::env::current_exe().ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(file_name)))
+        .unwrap_or_else(|| PathBuf::from(file_name))
+}
+
+fn controls_path() -> PathBuf {
+    data_path("controls.toml")
+}
+
+fn final_score(ecs: &World) -> (u32, u32) {
+    let gamedatas = ecs.read_storage::<components::GameData>();
+    gamedatas.join().next().map_or((0, 0), |gamedata| (gamedata.score, gamedata.level))
+}
+
+// Starts a fresh game, nothing pressed on the menus carries over into it.
+fn start_game(ecs: &mut World, input: &mut InputState) {
+    input.release_all();
+    input.end_tick();
+    game::new_game(ecs);
+}
+
+// Leaves the game over screen, from the keyboard or a controller. A score good enough for the
+// table asks for initials first, anything else goes back to the title.
+fn confirm_game_over<'a>(ecs: &World, state: &mut StateMachine, high_scores: &HighScoreTable, entered_name: &mut String, name_overlay: &mut Vec<UIElement<'a>>, font: &Font, texture_creator: &'a TextureCreator<WindowContext>) -> Result<(), String> {
+    let (score, _) = final_score(ecs);
+    if high_scores.qualifies(score) {
+        entered_name.clear();
+        *name_overlay = menus::name_entry_overlay(font, texture_creator, GAME_WIDTH as i32 / 2, score, entered_name)?;
+        state.transition(GameState::EnterName);
+    } else {
+        state.transition(GameState::Title);
+    }
+    Ok(())
+}
+
+// Puts the finished game into the high score table under the entered name, saves the table and
+// goes back to the title. Returns the title screen showing the new table.
+fn save_high_score<'a>(ecs: &World, state: &mut StateMachine, high_scores: &mut HighScoreTable, path: &Path, name: &str, font: &Font, texture_creator: &'a TextureCreator<WindowContext>) -> Result<Vec<UIElement<'a>>, String> {
+    let (score, level) = final_score(ecs);
+    high_scores.insert(HighScore{
+        name: name.to_string(),
+        score,
+        level,
+        date: highscores::today(),
+        seed: ecs.read_resource::<GameRng>().seed
+    });
+    if let Err(e) = high_scores.save(path) {
+        eprintln!("Could not save high scores: {}", e);
+    }
+    state.transition(GameState::Title);
+    menus::title_overlay(font, texture_creator, GAME_WIDTH as i32 / 2, high_scores)
+}
+
+fn open_settings(state: &mut StateMachine, controls_screen: &mut Option<ControlsScreen<'_>>, input: &mut InputState) {
+    // The menu takes over the keyboard, so nothing may stay held while it's open.
+    input.release_all();
+    *controls_screen = Some(ControlsScreen::new());
+    state.transition(GameState::Settings);
+}
+
+// Only the first game is recorded, it ends on game over or when quitting to the title.
+fn stop_recording(recorder: &mut Option<Recorder>, path: Option<&Path>) -> Result<(), String> {
+    if let (Some(mut recorder), Some(path)) = (recorder.take(), path) {
+        recorder.save(path)?;
+    }
+    Ok(())
+}
+
+// Returns the value following a command line flag, if the flag was given.
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Result<Option<&'a str>, String> {
+    match args.iter().position(|arg| arg == flag) {
+        Some(index) => args.get(index + 1)
+            .map(|value| Some(value.as_str()))
+            .ok_or(format!("{} needs a value", flag)),
+        None => Ok(None)
+    }
+}
+
+// The ship's, the UFO's and every weapon's collision shape is generated from the sprite's alpha channel.
+fn load_hulls(weapons: &Weapons) -> Result<Hulls, String> {
+    let mut hulls = Hulls::default();
+    let textures = ["img/ship.png", "img/ufo.png"].into_iter().chain(weapons.list.iter().map(|weapon| weapon.texture.as_str()));
+    for path in textures {
+        hulls.shapes.insert(path.to_string(), collision::load_hull(Path::new(path))?);
+    }
+    Ok(hulls)
+}
+
+// Parses an edge behavior flag such as --asteroid-edges wrap, keeping the default if it wasn't given.
+fn edges_arg(args: &[String], flag: &str, default: BoundaryBehavior) -> Result<BoundaryBehavior, String> {
+    match arg_value(args, flag)? {
+        Some(name) => BoundaryBehavior::from_name(name)
+            .ok_or(format!("invalid value for {}: \"{}\", expected wrap, bounce, despawn or clamp", flag, name)),
+        None => Ok(default)
+    }
+}
+
+// Usage: asteroid-game [--seed <n>] [--record <file> | --replay <file>] [--headless <ticks>] [--asteroid-collisions]
+//                      [--ship-edges <edges>] [--asteroid-edges <edges>] [--missile-edges <edges>]
 fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().collect();
+
+    let replay = match arg_value(&args, "--replay")? {
+        Some(path) => Some(Replay::load(Path::new(path))?),
+        None => None
+    };
+
+    // A replay only reproduces the session with the seed it was recorded with.
+    let seed = match (arg_value(&args, "--seed")?, replay.as_ref().and_then(|r| r.seed)) {
+        (_, Some(replay_seed)) => replay_seed,
+        (Some(value), None) => value.parse::<u64>().map_err(|e| format!("invalid seed: {}", e))?,
+        (None, None) => rand::random::<u64>()
+    };
+
+    // The same goes for the rules, the replay's replace the command line's.
+    let defaults = Rules::default();
+    let rules = match replay.as_ref().and_then(|r| r.rules) {
+        Some(replay_rules) => replay_rules,
+        None => Rules {
+            asteroid_collisions: args.iter().any(|arg| arg == "--asteroid-collisions"),
+            ship_edges: edges_arg(&args, "--ship-edges", defaults.ship_edges)?,
+            asteroid_edges: edges_arg(&args, "--asteroid-edges", defaults.asteroid_edges)?,
+            missile_edges: edges_arg(&args, "--missile-edges", defaults.missile_edges)?
+        }
+    };
+
+    let mut playback = replay.map(Playback::new);
+
+    let weapons = Weapons::load(Path::new("data/weapons.toml"))?;
+    let archetypes = Archetypes::load(Path::new("data/asteroids.toml"))?;
+
+    if let Some(value) = arg_value(&args, "--headless")? {
+        let ticks = value.parse::<u32>().map_err(|e| format!("invalid tick count: {}", e))?;
+        // Headless runs don't need the images, without them every sprite gets a simple shape.
+        let hulls = load_hulls(&weapons).unwrap_or_else(|e| {
+            eprintln!("{}, using simple collision shapes", e);
+            Hulls::default()
+        });
+
+        return headless::run(ticks, seed, rules, hulls, weapons, archetypes, playback);
+    }
+
+    let hulls = load_hulls(&weapons)?;
+
+    let record_path = arg_value(&args, "--record")?.map(Path::new);
+    let mut recorder = record_path.map(|_| Recorder::new(seed, rules));
+
     let sdl_context = sdl2::init()?;
     let video_subsystem = sdl_context.video()?;
 
@@ -82,8 +296,25 @@ fn main() -> Result<(), String> {
 
     // load images
     texture_manager.load("img/ship.png")?;
-    texture_manager.load("img/asteroid.png")?;
-    texture_manager.load("img/bullet.png")?;
+    texture_manager.load("img/ufo.png")?;
+    texture_manager.load("img/ufo_shot.png")?;
+    texture_manager.load("img/shield.png")?;
+    texture_manager.load("img/shockwave.png")?;
+    for kind in components::PowerUpKind::ALL {
+        texture_manager.load(kind.texture())?;
+    }
+    for weapon in &weapons.list {
+        texture_manager.load(weapon.texture.as_str())?;
+    }
+
+    // Asteroids that take a hit without breaking flash red.
+    let mut flash_textures: HashMap<String, Texture> = HashMap::new();
+    for archetype in &archetypes.list {
+        texture_manager.load(archetype.texture.as_str())?;
+        let mut flash_texture = texture_creator.load_texture(&archetype.texture)?;
+        flash_texture.set_color_mod(255, 90, 90);
+        flash_textures.insert(archetype.texture.clone(), flash_texture);
+    }
 
     // prepare fonts
     let ttf_context = sdl2::ttf::init().map_err(|e| e.to_string())?;
@@ -92,32 +323,65 @@ fn main() -> Result<(), String> {
 
 
     let mut event_pump = sdl_context.event_pump()?;
-    let mut key_manager: HashMap<String, bool> = HashMap::new();
+    let mut gamepads = Gamepads::new(sdl_context.game_controller()?);
+    let mut input_map = match InputMap::load(&controls_path(), |name| Keycode::from_name(name).is_some()) {
+        Ok(input_map) => input_map,
+        Err(e) => {
+            eprintln!("{}, using the default controls", e);
+            InputMap::default()
+        }
+    };
+    let mut controls_screen: Option<ControlsScreen> = None;
+    let mut input = InputState::default();
+    let mut replay_input = InputState::default();
+    let mut state = StateMachine::new();
 
     let mut gs = State {
-        ecs: World::new()
+        ecs: game::create_world(Arena::default(), seed)
     };
-    gs.ecs.register::<components::Position>();
-    gs.ecs.register::<components::Renderable>();
-    gs.ecs.register::<components::Player>();
-    gs.ecs.register::<components::Asteroid>();
-    gs.ecs.register::<components::Missile>();
-    gs.ecs.register::<components::GameData>();
-
-    let mut dispatcher = DispatcherBuilder::new()
-                                                    .with(asteroid::AsteroidMover, "asteroid_mover", &[])
-                                                    .with(asteroid::AsteroidCollider, "asteroid_collider", &[])
-                                                    .with(missile::MissileMover, "missile_mover", &[])
-                                                    .with(missile::MissileStriker, "missile_striker", &[])
-                                                    .build();
+
+    gs.ecs.insert(rules);
+    gs.ecs.insert(hulls);
+    gs.ecs.insert(weapons);
+    gs.ecs.insert(archetypes);
+
+    let mut dispatcher = game::build_dispatcher();
 
     game::load_world(&mut gs.ecs);
+
+    // A replay skips the title screen and starts playing right away. The game is started the same
+    // way a recorded one was, from the title screen, so both begin with the exact same world.
+    if playback.is_some() {
+        start_game(&mut gs.ecs, &mut input);
+        state.transition(GameState::Playing);
+    }
+
+    let color = Color::RGBA(15, 180, 75, 255);
+    let high_scores_path = data_path("highscores.toml");
+    let mut high_scores = HighScoreTable::load(&high_scores_path);
+    let mut title_overlay = menus::title_overlay(&font, &texture_creator, GAME_WIDTH as i32 / 2, &high_scores)?;
+    let mut entered_name = String::new();
+    let mut name_overlay: Vec<UIElement> = Vec::new();
+    let paused_overlay = vec![
+        ui::text_element(&font, &texture_creator, "PAUSED", color, GAME_WIDTH as i32 / 2 - 130, 150, 120)?,
+        ui::text_element(&font, &texture_creator, "Escape to resume, F1 for controls, Backspace to quit to the title", color, GAME_WIDTH as i32 / 2 - 440, 320, 40)?
+    ];
+    let game_over_overlay = vec![
+        ui::text_element(&font, &texture_creator, "GAME OVER", color, GAME_WIDTH as i32 / 2 - 220, 150, 120)?,
+        ui::text_element(&font, &texture_creator, "Press Enter to continue", color, GAME_WIDTH as i32 / 2 - 160, 320, 40)?
+    ];
+
     let mut frame_count: u32 = 0;
     let mut start_time = Instant::now();
     let mut fixed_step_time = Instant::now();
     let mut fps: f64 = 0.0;
 
     let mut current_score : u32 = 9999;
+    let mut current_lives : u32 = 0;
+    let mut current_weapon = String::new();
+    let mut current_hyperspace_ready = false;
+    let mut current_effects = String::new();
+    let mut current_bombs: u32 = 0;
     let mut ui_elements : Vec<UIElement> = Vec::new();
 
     'running: loop {
@@ -126,50 +390,141 @@ fn main() -> Result<(), String> {
                 Event::Quit {..} => {
                     break 'running;
                 },
-                Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
-                    break 'running;
-                },
-                Event::KeyDown { keycode: Some(Keycode::Space), .. } => {
-                    utils::key_down(&mut key_manager, " ".to_string());
-                },
-                Event::KeyUp { keycode: Some(Keycode::Space), .. } => {
-                    utils::key_up(&mut key_manager, " ".to_string());
-                },
-                Event::KeyDown { keycode: Some(Keycode::J), .. } => {
-                    utils::key_down(&mut key_manager, "J".to_string());
-                },
-                Event::KeyUp { keycode: Some(Keycode::J), .. } => {
-                    utils::key_up(&mut key_manager, "J".to_string());
-                },
-                Event::KeyDown { keycode, .. } =>{
-                    match keycode {
-                        None => {},
-                        Some(key) => {
-                            utils::key_down(&mut key_manager, key.to_string());
+                Event::KeyDown { keycode: Some(key), repeat: false, .. } => {
+                    match state.current() {
+                        GameState::Title => match key {
+                            Keycode::Return => {
+                                start_game(&mut gs.ecs, &mut input);
+                                state.transition(GameState::Playing);
+                            },
+                            Keycode::F1 => open_settings(&mut state, &mut controls_screen, &mut input),
+                            Keycode::Escape => break 'running,
+                            _ => {}
+                        },
+                        GameState::Playing => {
+                            match (key, input_map.action_for(&key.name())) {
+                                // Pausing is handled by the front end, the simulation never sees it.
+                                (Keycode::Escape, _) | (_, Some(Action::Pause)) => {
+                                    state.transition(GameState::Paused);
+                                },
+                                (_, Some(action)) => input.press(action),
+                                _ => {}
+                            }
+                        },
+                        GameState::Paused => {
+                            match (key, input_map.action_for(&key.name())) {
+                                (Keycode::Escape, _) | (Keycode::Return, _) | (_, Some(Action::Pause)) => {
+                                    state.transition(GameState::Playing);
+                                },
+                                (Keycode::F1, _) => open_settings(&mut state, &mut controls_screen, &mut input),
+                                (Keycode::Backspace, _) => {
+                                    stop_recording(&mut recorder, record_path)?;
+                                    state.transition(GameState::Title);
+                                },
+                                // Presses are ignored, or Fire and Hyperspace would go off on the
+                                // first tick after resuming. Releases still count, see KeyUp.
+                                _ => {}
+                            }
+                        },
+                        GameState::GameOver => {
+                            if key == Keycode::Return {
+                                confirm_game_over(&gs.ecs, &mut state, &high_scores, &mut entered_name, &mut name_overlay, &font, &texture_creator)?;
+                            }
+                        },
+                        GameState::EnterName => {
+                            let key_name = key.name();
+                            match key {
+                                Keycode::Return if !entered_name.is_empty() => {
+                                    title_overlay = save_high_score(&gs.ecs, &mut state, &mut high_scores, &high_scores_path, &entered_name, &font, &texture_creator)?;
+                                },
+                                Keycode::Backspace => {
+                                    entered_name.pop();
+                                },
+                                // Letter and number keys are named after their character.
+                                _ if key_name.len() == 1 && key_name.chars().all(|c| c.is_ascii_alphanumeric())
+                                    && entered_name.len() < MAX_NAME_LENGTH => {
+                                    entered_name.push_str(&key_name);
+                                },
+                                _ => {}
+                            }
+                            let (score, _) = final_score(&gs.ecs);
+                            name_overlay = menus::name_entry_overlay(&font, &texture_creator, GAME_WIDTH as i32 / 2, score, &entered_name)?;
+                        },
+                        GameState::Settings => {
+                            let close = controls_screen.as_mut().is_none_or(|screen| screen.handle_key(key, &mut input_map));
+                            if close {
+                                controls_screen = None;
+                                state.close_settings();
+                                if let Err(e) = input_map.save(&controls_path()) {
+                                    eprintln!("Could not save controls: {}", e);
+                                }
+                            }
                         }
                     }
                 },
-                Event::KeyUp{ keycode, .. } =>{
-                    match keycode {
-                        None => {},
-                        Some(key) => {
-                            utils::key_up(&mut key_manager, key.to_string());
-                        }
+                Event::KeyUp { keycode: Some(key), .. } => {
+                    // Releasing is harmless in every state and keeps nothing stuck after a menu.
+                    if let Some(action) = input_map.action_for(&key.name()) {
+                        input.release(action);
                     }
                 },
-                _ => {}
+                other => {
+                    // The controller's pause button doubles as confirm on the menus.
+                    if gamepads.handle_event(&other, &input_map, &mut input, state.runs_simulation()) {
+                        match state.current() {
+                            GameState::Title => {
+                                start_game(&mut gs.ecs, &mut input);
+                                state.transition(GameState::Playing);
+                            },
+                            GameState::Playing => { state.transition(GameState::Paused); },
+                            GameState::Paused => { state.transition(GameState::Playing); },
+                            GameState::GameOver => confirm_game_over(&gs.ecs, &mut state, &high_scores, &mut entered_name, &mut name_overlay, &font, &texture_creator)?,
+                            // There's no way to type with a controller, the score is saved under a placeholder.
+                            GameState::EnterName => {
+                                let name = highscores::name_or_default(&entered_name);
+                                title_overlay = save_high_score(&gs.ecs, &mut state, &mut high_scores, &high_scores_path, name, &font, &texture_creator)?;
+                            },
+                            GameState::Settings => {}
+                        }
+                    }
+                }
             }
         }
         frame_count += 1;
         let elapsed_time = start_time.elapsed().as_secs_f64();
         let fixed_step_elapsed_time = fixed_step_time.elapsed().as_secs_f64();
         
+        // Right after a bomb goes off the game runs at a third of its speed.
+        let step_time = if bomb::slow_motion(&gs.ecs) { 3.0/60.0 } else { 1.0/60.0 };
+
         // Used to seperate the game logic into a fixed timestep so that it's not affected by the current render frame rate.
-        if fixed_step_elapsed_time >= 1.0/60.0 {
-            game::update(&mut gs.ecs, &mut key_manager);
-            dispatcher.dispatch(&gs.ecs);
-            gs.ecs.maintain();
+        // The dispatcher only runs while playing, input changes on other screens are kept until the game resumes.
+        if fixed_step_elapsed_time >= step_time && state.runs_simulation() {
+            // Keyboard input is ignored during playback, the replay owns its own input state.
+            let tick_input = match &mut playback {
+                Some(playback) => {
+                    playback.apply(&mut replay_input);
+                    &mut replay_input
+                },
+                None => &mut input
+            };
+            if let Some(recorder) = &mut recorder {
+                recorder.record(tick_input);
+            }
+
+            game::step(&mut gs.ecs, &mut dispatcher, tick_input);
             fixed_step_time = Instant::now();
+
+            // Once the recorded length has been played back the replay is over, the player takes over from the title screen.
+            if playback.as_ref().is_some_and(|playback| playback.finished()) {
+                playback = None;
+                state.transition(GameState::Title);
+            }
+
+            if game::is_game_over(&gs.ecs) {
+                stop_recording(&mut recorder, record_path)?;
+                state.transition(GameState::GameOver);
+            }
         }
 
         // Fps counter only used 
@@ -181,16 +536,44 @@ fn main() -> Result<(), String> {
         
         {
         let gamedatas = gs.ecs.read_storage::<components::GameData>();
+        // While there's no ship the last weapon stays on screen.
+        let new_weapon = gs.ecs.read_storage::<components::Player>().join().next()
+            .map(|player| gs.ecs.read_resource::<Weapons>().get(player.weapon).name.clone())
+            .unwrap_or_else(|| current_weapon.clone());
+        let hyperspace_ready = gs.ecs.read_storage::<components::Player>().join().next()
+            .is_some_and(|player| player.hyperspace_cooldown == 0);
+        let bombs = gs.ecs.read_storage::<components::Player>().join().next()
+            .map_or(current_bombs, |player| player.bombs);
+        let combo = gs.ecs.read_storage::<components::Combo>().join().next()
+            .map_or(1, |combo| score::combo_multiplier(combo.hits));
+        // Timed power-ups with the seconds they have left, and the combo once it multiplies the score.
+        let effects = gs.ecs.read_storage::<components::ActiveEffects>().join().next()
+            .map(|effects| {
+                let mut shown: Vec<String> = [("Rapid fire", effects.rapid_fire), ("Triple shot", effects.triple_shot), ("Score x2", effects.score_multiplier)].iter()
+                    .filter(|(_, ticks)| *ticks > 0)
+                    .map(|(name, ticks)| format!("{} {}", name, ticks.div_ceil(60)))
+                    .collect();
+                if combo > 1 {
+                    shown.push(format!("Combo x{}", combo));
+                }
+                shown.join("  ")
+            })
+            .unwrap_or_default();
 
         for gamedata in (gamedatas).join() {
             let new_score = gamedata.score;
     
             // If score hasn't changed then we don't need to create a new UI Element.
-            if new_score == current_score && frame_count != 0{
+            if new_score == current_score && gamedata.lives == current_lives && new_weapon == current_weapon && hyperspace_ready == current_hyperspace_ready && effects == current_effects && bombs == current_bombs && frame_count != 0{
                 continue;
             }
 
             current_score = new_score;
+            current_lives = gamedata.lives;
+            current_weapon = new_weapon.clone();
+            current_hyperspace_ready = hyperspace_ready;
+            current_effects = effects.clone();
+            current_bombs = bombs;
 
             // Clears the UI Element so that we can replace it with one reflecting the new score.
 
@@ -244,16 +627,78 @@ fn main() -> Result<(), String> {
             let asteroid_count_ui : UIElement = UIElement { texture: fps_texture, position: fps_target };
 
             ui_elements.push(asteroid_count_ui);
+
+            // Shown so a run can be reproduced with --seed.
+            let seed_display = "Seed: ".to_string() + &seed.to_string();
+
+            let seed_surface = font
+                    .render(&seed_display)
+                    .blended(color)
+                    .map_err(|e| e.to_string())?;
+
+            // The seed can be up to 20 digits, so keep the text's aspect ratio instead of a fixed width.
+            let seed_width = seed_surface.width() * 30 / seed_surface.height();
+            let seed_texture : Texture = texture_creator
+                    .create_texture_from_surface(seed_surface)
+                    .map_err(|e| e.to_string())?;
+            let seed_target = Rect::new(10 as i32, 120 as i32, seed_width, 30 as u32);
+
+            let seed_ui : UIElement = UIElement { texture: seed_texture, position: seed_target };
+
+            ui_elements.push(seed_ui);
+
+            let lives_display = "Lives: ".to_string() + &current_lives.to_string();
+            ui_elements.push(ui::text_element(&font, &texture_creator, &lives_display, color, GAME_WIDTH as i32 - 150, 0, 50)?);
+
+            let weapon_display = "Weapon: ".to_string() + &current_weapon;
+            ui_elements.push(ui::text_element(&font, &texture_creator, &weapon_display, color, GAME_WIDTH as i32 - 250, 40, 40)?);
+
+            ui_elements.push(ui::text_element(&font, &texture_creator, "Shield", color, GAME_WIDTH as i32 - 250, 80, 40)?);
+
+            let hyperspace_display = if current_hyperspace_ready { "Hyperspace: ready" } else { "Hyperspace: charging" };
+            ui_elements.push(ui::text_element(&font, &texture_creator, hyperspace_display, color, GAME_WIDTH as i32 - 250, 120, 40)?);
+
+            let bombs_display = "Bombs: ".to_string() + &current_bombs.to_string();
+            ui_elements.push(ui::text_element(&font, &texture_creator, &bombs_display, color, GAME_WIDTH as i32 - 250, 160, 40)?);
+
+            if !current_effects.is_empty() {
+                ui_elements.push(ui::text_element(&font, &texture_creator, &current_effects, color, GAME_WIDTH as i32 / 2 - 200, 0, 40)?);
+            }
         }
 
 
 
+        }
+
+        // Score popups move every tick, so their text is made again each frame.
+        let mut popups: Vec<UIElement> = Vec::new();
+        {
+            let positions = gs.ecs.read_storage::<components::Position>();
+            let score_popups = gs.ecs.read_storage::<components::ScorePopup>();
+            for (pos, popup) in (&positions, &score_popups).join() {
+                let mut popup_ui = ui::text_element(&font, &texture_creator, &format!("+{}", popup.points), color, pos.x as i32, pos.y as i32 - 15, 30)?;
+                popup_ui.position.set_x(pos.x as i32 - popup_ui.position.width() as i32 / 2);
+                popups.push(popup_ui);
+            }
         }
 
         // Renders all the textures to the window.
-        render(&mut canvas, &mut texture_manager, &gs.ecs, current_score, &ui_elements)?;
+        let overlay = match state.current() {
+            GameState::Title => Some(&title_overlay),
+            GameState::Playing => None,
+            GameState::Paused => Some(&paused_overlay),
+            GameState::GameOver => Some(&game_over_overlay),
+            GameState::EnterName => Some(&name_overlay),
+            GameState::Settings => match &mut controls_screen {
+                Some(screen) => Some(screen.ui_elements(&font, &texture_creator, &input_map)?),
+                None => None
+            }
+        };
+        render(&mut canvas, &mut texture_manager, &flash_textures, &gs.ecs, &ui_elements, &popups, overlay)?;
     }
 
+    stop_recording(&mut recorder, record_path)?;
+
     Ok(())
 
 }
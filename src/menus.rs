@@ -0,0 +1,44 @@
+use sdl2::pixels::Color;
+use sdl2::render::TextureCreator;
+use sdl2::ttf::Font;
+use sdl2::video::WindowContext;
+
+use asteroid_game::highscores::{HighScoreTable, MAX_NAME_LENGTH};
+
+use crate::ui::{self, UIElement};
+
+const COLOR: Color = Color::RGBA(15, 180, 75, 255);
+
+// Title, help line and the high score table below them.
+pub fn title_overlay<'a>(font: &Font, texture_creator: &'a TextureCreator<WindowContext>, center_x: i32, high_scores: &HighScoreTable) -> Result<Vec<UIElement<'a>>, String> {
+    let mut elements = vec![
+        ui::text_element(font, texture_creator, "ASTEROIDS", COLOR, center_x - 200, 40, 120)?,
+        ui::text_element(font, texture_creator, "Enter to start, F1 for controls, Escape to quit", COLOR, center_x - 330, 170, 40)?
+    ];
+
+    if high_scores.scores().is_empty() {
+        elements.push(ui::text_element(font, texture_creator, "No high scores yet", COLOR, center_x - 120, 250, 30)?);
+    }
+
+    for (place, entry) in high_scores.scores().iter().enumerate() {
+        let line = format!("{:>2}. {:<3} {:>7}  level {:<3} {}  seed {}", place + 1, entry.name, entry.score, entry.level, entry.date, entry.seed);
+        elements.push(ui::text_element(font, texture_creator, &line, COLOR, center_x - 300, 240 + 36 * place as i32, 30)?);
+    }
+
+    Ok(elements)
+}
+
+pub fn name_entry_overlay<'a>(font: &Font, texture_creator: &'a TextureCreator<WindowContext>, center_x: i32, score: u32, name: &str) -> Result<Vec<UIElement<'a>>, String> {
+    // Show the empty slots so it's clear how many letters are left.
+    let mut shown = name.to_string();
+    while shown.len() < MAX_NAME_LENGTH {
+        shown.push('_');
+    }
+
+    Ok(vec![
+        ui::text_element(font, texture_creator, "NEW HIGH SCORE", COLOR, center_x - 300, 120, 120)?,
+        ui::text_element(font, texture_creator, &score.to_string(), COLOR, center_x - 60, 250, 60)?,
+        ui::text_element(font, texture_creator, &shown, COLOR, center_x - 60, 330, 100)?,
+        ui::text_element(font, texture_creator, "Type your initials, Enter to save (controller: Pause)", COLOR, center_x - 230, 460, 40)?
+    ])
+}
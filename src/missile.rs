@@ -1,89 +1,156 @@
 use specs::prelude::*;
 use specs::{Entities, Join};
+use specs::shred::ResourceId;
+use vector2d::Vector2D;
+use std::collections::{HashMap, HashSet, VecDeque};
 
+use crate::archetypes::Archetypes;
+use crate::collision::{self, Shape};
 use crate::components;
+use crate::powerup::Drops;
+use crate::resources::Rules;
+use crate::score::{self, Scoring};
+use crate::spatial::SpatialGrid;
 
-pub struct MissileMover;
+// Ticks an asteroid stays tinted after a hit it survived.
+const HIT_FLASH_TICKS: u32 = 6;
 
-impl<'a> System<'a> for MissileMover {
-    type SystemData = (
-        WriteStorage<'a, components::Position>,
-        WriteStorage<'a, components::Renderable>,
-        ReadStorage<'a, components::Missile>,
-        Entities<'a>
-    );
+// Checks the whole path a missile's center took during the tick against the asteroids' bounding
+// circles, so fast missiles can't skip over small asteroids, and the missile's collider where it
+// ended up. A missile damages the first asteroid on its path, a piercing one every asteroid on it.
+// Asteroids whose health runs out break up following their kind's SplitRule.
+pub struct MissileStriker;
 
-    fn run(&mut self, data: Self::SystemData) {
-        let(mut positions, mut renderables, missiles, entities) = data;
+// Everything MissileStriker works with. Strikes::deal only reads it while the hits are worked out,
+// the results are written back once they're all known.
+#[derive(SystemData)]
+pub struct StrikerData<'a> {
+    positions: WriteStorage<'a, components::Position>,
+    rends: WriteStorage<'a, components::Renderable>,
+    missiles: WriteStorage<'a, components::Missile>,
+    asteroids: WriteStorage<'a, components::Asteroid>,
+    velocities: WriteStorage<'a, components::Velocity>,
+    behaviors: WriteStorage<'a, components::BoundaryBehavior>,
+    previous_positions: WriteStorage<'a, components::PreviousPosition>,
+    colliders: WriteStorage<'a, components::Collider>,
+    healths: WriteStorage<'a, components::Health>,
+    flashes: WriteStorage<'a, components::HitFlash>,
+    explosives: WriteStorage<'a, components::Explosive>,
+    chillings: WriteStorage<'a, components::Chilling>,
+    grid: ReadExpect<'a, SpatialGrid>,
+    rules: ReadExpect<'a, Rules>,
+    archetypes: ReadExpect<'a, Archetypes>,
+    drops: WriteExpect<'a, Drops>,
+    scoring: WriteExpect<'a, Scoring>,
+    entities: Entities<'a>
+}
 
-        for(pos, rend, missile, entity) in (&mut positions, &mut renderables, &missiles, &entities).join() {
-            let radian = pos.rot.to_radians();
+impl<'a> System<'a> for MissileStriker {
+    type SystemData = StrikerData<'a>;
 
-            let move_x = missile.speed * radian.sin();
-            let move_y = missile.speed * radian.cos();
-            pos.x += move_x;
-            pos.y -= move_y;
+    fn run(&mut self, mut data: Self::SystemData) {
+        let mut strikes = Strikes::default();
+        // Asteroids each piercing missile went into this tick.
+        let mut pierced = Vec::<(Entity, Vec<Entity>)>::new();
 
-            if pos.x > crate::GAME_WIDTH.into() || pos.x < 0.0 || pos.y > crate::GAME_HEIGHT.into() || pos.y < 0.0 {
-                entities.delete(entity).ok();
-            }
+        for (missile_pos, missile_rend, missile, missile_entity) in (&data.positions, &data.rends, &data.missiles, &data.entities).join() {
+            // Without a previous position only the current one is checked.
+            let missile_end = Vector2D::new(missile_pos.x, missile_pos.y);
+            let missile_start = data.previous_positions.get(missile_entity).map_or(missile_end, |previous| Vector2D::new(previous.x, previous.y));
+            // Without a collider the missile is only a point.
+            let missile_shape = data.colliders.get(missile_entity).map(|collider| &collider.shape);
+            let missile_radius = missile_shape.map_or(0.0, |shape| shape.bounding_radius());
 
-            rend.rot = pos.rot;
-        }
-    }
-}
+            let mut hits: Vec<(f64, Entity)> = Vec::new();
+            data.grid.query_path((missile_start.x, missile_start.y), (missile_end.x, missile_end.y), missile_radius, |candidate| {
+                // Asteroids a shockwave destroyed this tick aren't asteroids anymore, see bomb::ShockwaveBlaster.
+                if strikes.destroyed.contains(&candidate.entity) || missile.pierced.contains(&candidate.entity) || !data.asteroids.contains(candidate.entity) {
+                    return;
+                }
 
-pub struct MissileStriker;
+                // Relative to the asteroid, which moved during the tick as well.
+                let asteroid_end = data.positions.get(candidate.entity).map_or(Vector2D::new(candidate.x, candidate.y), |pos| Vector2D::new(pos.x, pos.y));
+                let asteroid_start = data.previous_positions.get(candidate.entity).map_or(asteroid_end, |previous| Vector2D::new(previous.x, previous.y));
 
-impl<'a> System<'a> for MissileStriker {
-    type SystemData = (
-        WriteStorage<'a, components::Position>,
-        WriteStorage<'a, components::Renderable>,
-        WriteStorage<'a, components::Missile>,
-        WriteStorage<'a, components::Asteroid>,
-        WriteStorage<'a, components::Player>,
-        WriteStorage<'a, components::GameData>,
-        Entities<'a>
-    );
+                let mut time = collision::segment_hits_circle(missile_start - asteroid_start, missile_end - asteroid_end, candidate.radius);
 
-    fn run(&mut self, data: Self::SystemData) {
-        let (positions, rends, missiles, asteroids, _players, _, entities) = &data;
-        let mut asteroid_creation = Vec::<components::PendingAsteroid>::new();
-        let mut score: u32 = 0;
-
-        for (missile_pos, _, _, missile_entity) in (positions, rends, missiles, entities).join() {
-            for (asteroid_pos, asteroid_rend, _, asteroid_entity) in (positions, rends, asteroids, entities).join()  {
-                let diff_x: f64 = (missile_pos.x - asteroid_pos.x).abs();
-                let diff_y: f64 = (missile_pos.y - asteroid_pos.y).abs();
-                
-                // Hypotenuse of differences
-                let hyp: f64 = ((diff_x*diff_x) + (diff_y*diff_y)).sqrt();
-
-                if hyp < asteroid_rend.o_w as f64 / 2.0 {
-                    score += 10;
-
-                    entities.delete(missile_entity).ok();
-                    entities.delete(asteroid_entity).ok();
-
-                    let new_size = asteroid_rend.o_w/2;
-                    if new_size >= 25 {
-                        asteroid_creation.push(components::PendingAsteroid{x: asteroid_pos.x, y: asteroid_pos.y, rot: asteroid_pos.rot - 90.0, size: new_size});
-                        asteroid_creation.push(components::PendingAsteroid{x: asteroid_pos.x, y: asteroid_pos.y, rot: asteroid_pos.rot + 90.0, size: new_size});
+                // The tip or the side of the missile can touch an asteroid its center missed.
+                if let (None, Some(missile_shape)) = (time, missile_shape) {
+                    let asteroid_fallback = Shape::Circle{radius: candidate.radius};
+                    let asteroid_shape = data.colliders.get(candidate.entity).map_or(&asteroid_fallback, |collider| &collider.shape);
+                    let asteroid_rot = data.rends.get(candidate.entity).map_or(0.0, |rend| rend.rot);
+                    if collision::shapes_overlap(missile_shape, missile_end, missile_rend.rot, asteroid_shape, asteroid_end, asteroid_rot) {
+                        time = Some(1.0);
                     }
                 }
+
+                if let Some(time) = time {
+                    hits.push((time, candidate.entity));
+                }
+            });
+
+            hits.sort_by(|a, b| a.0.total_cmp(&b.0));
+            if missile.piercing {
+                pierced.push((missile_entity, hits.iter().map(|(_, asteroid_entity)| *asteroid_entity).collect()));
+            } else {
+                hits.truncate(1);
             }
+
+            for (_, asteroid_entity) in hits {
+                if !missile.piercing {
+                    data.entities.delete(missile_entity).ok();
+                }
+
+                // UFO shots break asteroids too, but only the player scores and gets power-ups.
+                if !missile.hostile {
+                    strikes.hits.push(missile_entity);
+                }
+                strikes.deal(&data, asteroid_entity, missile.damage, !missile.hostile);
+            }
+        }
+
+        data.drops.positions.extend(strikes.dropped_at);
+        data.scoring.hits.extend(strikes.hits);
+        data.scoring.kills.extend(strikes.kills);
+
+        // Asteroids that survived keep the damage and flash.
+        for (asteroid_entity, taken) in strikes.damage_taken {
+            if strikes.destroyed.contains(&asteroid_entity) {
+                continue;
+            }
+            if let Some(health) = data.healths.get_mut(asteroid_entity) {
+                health.current = health.current.saturating_sub(taken);
+            }
+            data.flashes.insert(asteroid_entity, components::HitFlash{ticks: HIT_FLASH_TICKS}).ok();
         }
 
-        let(mut positions, mut rends, _, mut asteroids, _, _, entities) = data;
+        for (missile_entity, asteroid_entities) in pierced {
+            if let Some(missile) = data.missiles.get_mut(missile_entity) {
+                missile.pierced.extend(asteroid_entities);
+            }
+        }
 
-        for new_asteroid in asteroid_creation {
-            let new_ast = entities.create();
-            positions.insert(new_ast, components::Position{x: new_asteroid.x, y: new_asteroid.y, rot: new_asteroid.rot}).ok();
-            asteroids.insert(new_ast, components::Asteroid{speed: 2.5, rot_speed: 0.5}).ok();
-            rends.insert(new_ast, components::Renderable{
-                tex_name: String::from("img/asteroid.png"),
-                i_w: 256,
-                i_h: 256,
+        for new_asteroid in strikes.asteroid_creation {
+            let archetype = data.archetypes.get(&new_asteroid.kind);
+            let health = archetype.health(new_asteroid.size);
+            let new_ast = data.entities.create();
+            data.positions.insert(new_ast, components::Position{x: new_asteroid.x, y: new_asteroid.y, rot: 0.0}).ok();
+            data.previous_positions.insert(new_ast, components::PreviousPosition{x: new_asteroid.x, y: new_asteroid.y}).ok();
+            data.asteroids.insert(new_ast, components::Asteroid{rot_speed: archetype.rot_speed, kind: new_asteroid.kind}).ok();
+            if let Some(explosive) = &archetype.explosive {
+                data.explosives.insert(new_ast, explosive.clone()).ok();
+            }
+            if let Some(chilling) = &archetype.chilling {
+                data.chillings.insert(new_ast, chilling.clone()).ok();
+            }
+            data.healths.insert(new_ast, components::Health{current: health, max: health}).ok();
+            data.velocities.insert(new_ast, components::Velocity(new_asteroid.velocity)).ok();
+            data.behaviors.insert(new_ast, data.rules.asteroid_edges).ok();
+            data.colliders.insert(new_ast, components::Collider{shape: Shape::Circle{radius: new_asteroid.size as f64 / 2.0}}).ok();
+            data.rends.insert(new_ast, components::Renderable{
+                tex_name: archetype.texture.clone(),
+                i_w: archetype.texture_width,
+                i_h: archetype.texture_height,
                 o_w: new_asteroid.size,
                 o_h: new_asteroid.size,
                 frame: 0,
@@ -91,10 +158,135 @@ impl<'a> System<'a> for MissileStriker {
                 rot: 0.0
             }).ok();
         }
+    }
+}
+
+// What the missiles' hits on asteroids during a tick came to.
+#[derive(Default)]
+struct Strikes {
+    // Player missiles that hit, once for every asteroid they hit
+    hits: Vec<Entity>,
+    // Where the player destroyed asteroids and what they're worth, see score::ScoreKeeper
+    kills: Vec<(f64, f64, u32)>,
+    // Damage taken this tick, several hits on an asteroid in the same tick add up.
+    damage_taken: HashMap<Entity, u32>,
+    // An asteroid can only be destroyed once, even when two missiles reach it in the same tick.
+    destroyed: HashSet<Entity>,
+    asteroid_creation: Vec<components::PendingAsteroid>,
+    // Where the player destroyed asteroids, they may leave a power-up behind.
+    dropped_at: Vec<(f64, f64)>
+}
+
+impl Strikes {
+    // Damages an asteroid, destroying it once its health runs out and breaking it up following
+    // its kind's SplitRule. Only the player's hits score.
+    fn deal(&mut self, data: &StrikerData, asteroid_entity: Entity, damage: u32, by_player: bool) {
+        let StrikerData{positions, rends, asteroids, velocities, healths, explosives, grid, archetypes, entities, ..} = data;
+
+        // An explosion damages the asteroids around it, which can set off more explosions.
+        let mut damage_queue = VecDeque::from([(asteroid_entity, damage)]);
+        while let Some((asteroid_entity, damage)) = damage_queue.pop_front() {
+            if self.destroyed.contains(&asteroid_entity) {
+                continue;
+            }
+            let (asteroid_pos, asteroid_rend, asteroid_velocity, asteroid) = match (positions.get(asteroid_entity), rends.get(asteroid_entity), velocities.get(asteroid_entity), asteroids.get(asteroid_entity)) {
+                (Some(pos), Some(rend), Some(velocity), Some(asteroid)) => (pos, rend, velocity, asteroid),
+                _ => continue
+            };
+
+            // Without a Health an asteroid goes down to any hit.
+            let health = healths.get(asteroid_entity).map_or(1, |health| health.current);
+            let taken = self.damage_taken.entry(asteroid_entity).or_insert(0);
+            *taken = taken.saturating_add(damage);
+            if *taken < health {
+                continue;
+            }
+
+            if by_player {
+                self.kills.push((asteroid_pos.x, asteroid_pos.y, score::asteroid_points(asteroid_rend.o_w)));
+                self.dropped_at.push((asteroid_pos.x, asteroid_pos.y));
+            }
+
+            entities.delete(asteroid_entity).ok();
+            self.destroyed.insert(asteroid_entity);
+
+            if let Some(explosive) = explosives.get(asteroid_entity) {
+                grid.query(asteroid_pos.x, asteroid_pos.y, explosive.radius, |neighbour| {
+                    if neighbour.entity != asteroid_entity {
+                        damage_queue.push_back((neighbour.entity, explosive.damage));
+                    }
+                });
+            }
 
-        let (_, _, _, _, _, mut gamedatas, _) = data;
-        for mut gamedata in (&mut gamedatas).join() {
-            gamedata.score += score;
+            let split = &archetypes.get(&asteroid.kind).split;
+            let new_size = asteroid_rend.o_w/2;
+            if split.count > 0 && new_size >= split.min_size {
+                // The pieces fly off fanned out around the asteroid's heading, starting half
+                // their size away from its center.
+                let speed = asteroid_velocity.0.length();
+                let heading = if speed > 0.0 { asteroid_velocity.0.x.atan2(-asteroid_velocity.0.y).to_degrees() } else { 0.0 };
+                let kind = split.kind.as_ref().unwrap_or(&asteroid.kind);
+                for piece in 0..split.count {
+                    let angle = if split.count > 1 {
+                        heading + split.spread * (piece as f64 / (split.count - 1) as f64 - 0.5)
+                    } else {
+                        heading
+                    };
+                    let radians = angle.to_radians();
+                    let direction = Vector2D::new(radians.sin(), -radians.cos());
+                    let offset = direction * (new_size as f64 / 2.0);
+                    self.asteroid_creation.push(components::PendingAsteroid{
+                        x: asteroid_pos.x + offset.x,
+                        y: asteroid_pos.y + offset.y,
+                        velocity: direction * (speed * split.speed),
+                        size: new_size,
+                        kind: kind.clone()
+                    });
+                }
+            }
+        }
+    }
+}
+
+// Turns homing missiles towards the nearest asteroid, by at most their turn rate per tick. They
+// keep their speed, and the sprite turns along with them.
+pub struct MissileHoming;
+
+impl<'a> System<'a> for MissileHoming {
+    type SystemData = (
+        WriteStorage<'a, components::Position>,
+        WriteStorage<'a, components::Velocity>,
+        WriteStorage<'a, components::Renderable>,
+        ReadStorage<'a, components::Homing>,
+        ReadStorage<'a, components::Asteroid>
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut positions, mut velocities, mut rends, homings, asteroids) = data;
+        let targets: Vec<Vector2D<f64>> = (&positions, &asteroids).join()
+            .map(|(pos, _)| Vector2D::new(pos.x, pos.y))
+            .collect();
+
+        for (pos, velocity, rend, homing) in (&mut positions, &mut velocities, &mut rends, &homings).join() {
+            let here = Vector2D::new(pos.x, pos.y);
+            let nearest = targets.iter()
+                .copied()
+                .min_by(|a, b| (*a - here).length_squared().total_cmp(&(*b - here).length_squared()));
+            let Some(target) = nearest else {
+                continue;
+            };
+
+            // Heading towards the target, 0 is up and angles grow clockwise like Position.rot.
+            let offset = target - here;
+            let wanted = offset.x.atan2(-offset.y).to_degrees();
+            // The shorter way round, from -180 to 180 degrees.
+            let turn = (wanted - pos.rot + 540.0).rem_euclid(360.0) - 180.0;
+            pos.rot = (pos.rot + turn.clamp(-homing.turn_rate, homing.turn_rate)).rem_euclid(360.0);
+
+            let speed = velocity.0.length();
+            let radians = pos.rot.to_radians();
+            velocity.0 = Vector2D::new(speed * radians.sin(), -speed * radians.cos());
+            rend.rot = pos.rot;
         }
     }
 }
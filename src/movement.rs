@@ -0,0 +1,156 @@
+use specs::{System, ReadStorage, WriteStorage, ReadExpect, WriteExpect, Join, LendJoin, Entities};
+
+use crate::components::{self, BoundaryBehavior};
+use crate::resources::Arena;
+use crate::score::Scoring;
+
+// Moves everything with a Velocity. The only place positions are integrated, systems that
+// steer or bounce entities change their Velocity instead.
+pub struct Mover;
+
+impl<'a> System<'a> for Mover {
+    type SystemData = (
+        WriteStorage<'a, components::Position>,
+        ReadStorage<'a, components::Velocity>,
+        WriteStorage<'a, components::PreviousPosition>
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut positions, velocities, mut previous_positions) = data;
+
+        for (pos, velocity, previous) in (&mut positions, &velocities, (&mut previous_positions).maybe()).join() {
+            if let Some(previous) = previous {
+                previous.x = pos.x;
+                previous.y = pos.y;
+            }
+
+            pos.x += velocity.0.x;
+            pos.y += velocity.0.y;
+        }
+    }
+}
+
+// A player missile that ends without having hit anything breaks the combo, see score::ScoreKeeper.
+fn record_miss(missile: Option<&components::Missile>, entity: specs::Entity, scoring: &mut Scoring) {
+    if missile.is_some_and(|missile| !missile.hostile && missile.pierced.is_empty()) {
+        scoring.misses.push(entity);
+    }
+}
+
+// Applies each entity's BoundaryBehavior once it has moved.
+pub struct Boundaries;
+
+impl<'a> System<'a> for Boundaries {
+    type SystemData = (
+        WriteStorage<'a, components::Position>,
+        WriteStorage<'a, components::Velocity>,
+        WriteStorage<'a, components::PreviousPosition>,
+        ReadStorage<'a, components::Renderable>,
+        ReadStorage<'a, BoundaryBehavior>,
+        ReadStorage<'a, components::Missile>,
+        ReadExpect<'a, Arena>,
+        WriteExpect<'a, Scoring>,
+        Entities<'a>
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut positions, mut velocities, mut previous_positions, rends, behaviors, missiles, arena, mut scoring, entities) = data;
+        let arena_width = arena.width as f64;
+        let arena_height = arena.height as f64;
+
+        for (pos, velocity, previous, rend, behavior, entity) in (&mut positions, &mut velocities, (&mut previous_positions).maybe(), &rends, &behaviors, &entities).join() {
+            let velocity = &mut velocity.0;
+            let half_width = (rend.o_w / 2) as f64;
+            let half_height = (rend.o_h / 2) as f64;
+
+            match behavior {
+                BoundaryBehavior::Wrap => {
+                    let (old_x, old_y) = (pos.x, pos.y);
+                    if pos.x > arena_width {
+                        pos.x -= arena_width;
+                    }
+                    if pos.x < 0.0 {
+                        pos.x += arena_width;
+                    }
+                    if pos.y > arena_height {
+                        pos.y -= arena_height;
+                    }
+                    if pos.y < 0.0 {
+                        pos.y += arena_height;
+                    }
+
+                    // Move the previous position along, so the last step doesn't look like
+                    // a jump across the whole arena.
+                    if let Some(previous) = previous {
+                        previous.x += pos.x - old_x;
+                        previous.y += pos.y - old_y;
+                    }
+                },
+                BoundaryBehavior::Bounce => {
+                    // Only turn around when heading out, an entity pushed into the wall by another
+                    // one would otherwise flip every tick and get stuck.
+                    if (pos.x > arena_width - half_width && velocity.x > 0.0)
+                        || (pos.x < half_width && velocity.x < 0.0) {
+                        velocity.x = -velocity.x;
+                    }
+                    if (pos.y > arena_height - half_height && velocity.y > 0.0)
+                        || (pos.y < half_height && velocity.y < 0.0) {
+                        velocity.y = -velocity.y;
+                    }
+                },
+                BoundaryBehavior::Despawn => {
+                    if pos.x > arena_width || pos.x < 0.0 || pos.y > arena_height || pos.y < 0.0 {
+                        entities.delete(entity).ok();
+                        record_miss(missiles.get(entity), entity, &mut scoring);
+                    }
+                },
+                BoundaryBehavior::Clamp => {
+                    if pos.x > arena_width - half_width || pos.x < half_width {
+                        pos.x = pos.x.clamp(half_width, arena_width - half_width);
+                        velocity.x = 0.0;
+                    }
+                    if pos.y > arena_height - half_height || pos.y < half_height {
+                        pos.y = pos.y.clamp(half_height, arena_height - half_height);
+                        velocity.y = 0.0;
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Counts down each Lifetime by one tick and by the distance moved, deleting the entity once
+// either limit is used up.
+pub struct Expiry;
+
+impl<'a> System<'a> for Expiry {
+    type SystemData = (
+        WriteStorage<'a, components::Lifetime>,
+        ReadStorage<'a, components::Velocity>,
+        ReadStorage<'a, components::Missile>,
+        WriteExpect<'a, Scoring>,
+        Entities<'a>
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut lifetimes, velocities, missiles, mut scoring, entities) = data;
+
+        for (lifetime, velocity, entity) in (&mut lifetimes, (&velocities).maybe(), &entities).join() {
+            let mut expired = false;
+            if let Some(ticks) = &mut lifetime.ticks {
+                *ticks = ticks.saturating_sub(1);
+                expired |= *ticks == 0;
+            }
+
+            if let (Some(distance), Some(velocity)) = (&mut lifetime.distance, velocity) {
+                *distance -= velocity.0.length();
+                expired |= *distance <= 0.0;
+            }
+
+            if expired {
+                entities.delete(entity).ok();
+                record_miss(missiles.get(entity), entity, &mut scoring);
+            }
+        }
+    }
+}
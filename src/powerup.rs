@@ -0,0 +1,198 @@
+use rand::Rng;
+use specs::prelude::*;
+use specs::{Entities, Join};
+use vector2d::Vector2D;
+
+use crate::collision::{self, Shape};
+use crate::components::{self, PowerUpKind};
+use crate::bomb;
+use crate::game;
+use crate::resources::GameRng;
+
+// Percent chance of an asteroid the player destroys dropping a power-up.
+pub const DROP_CHANCE: u32 = 8;
+// Ticks a power-up floats around before it's gone, it blinks during the last BLINK_TICKS.
+pub const POWERUP_LIFETIME: u32 = 600;
+pub const BLINK_TICKS: u32 = 120;
+// Pixels per tick a power-up drifts at, in a random direction.
+const DRIFT_SPEED: f64 = 0.8;
+const POWERUP_SIZE: u32 = 32;
+// Ticks a timed power-up lasts.
+pub const EFFECT_TICKS: u32 = 600;
+// Rapid fire divides the weapon's cooldown by this and lets this many times more of its shots fly at once.
+pub const RAPID_FIRE_FACTOR: u32 = 3;
+// Degrees off each shot the two extra shots of triple shot fly at.
+pub const TRIPLE_SHOT_ANGLE: f64 = 15.0;
+pub const SCORE_MULTIPLIER: u32 = 2;
+
+impl PowerUpKind {
+    pub const ALL: [PowerUpKind; 6] = [
+        PowerUpKind::RapidFire,
+        PowerUpKind::TripleShot,
+        PowerUpKind::ShieldRecharge,
+        PowerUpKind::ExtraLife,
+        PowerUpKind::ScoreMultiplier,
+        PowerUpKind::Bomb
+    ];
+
+    pub fn texture(self) -> &'static str {
+        match self {
+            PowerUpKind::RapidFire => "img/powerup_rapid_fire.png",
+            PowerUpKind::TripleShot => "img/powerup_triple_shot.png",
+            PowerUpKind::ShieldRecharge => "img/powerup_shield_recharge.png",
+            PowerUpKind::ExtraLife => "img/powerup_extra_life.png",
+            PowerUpKind::ScoreMultiplier => "img/powerup_score_multiplier.png",
+            PowerUpKind::Bomb => "img/powerup_bomb.png"
+        }
+    }
+
+    // How often it drops compared to the other kinds
+    fn weight(self) -> u32 {
+        match self {
+            PowerUpKind::RapidFire | PowerUpKind::TripleShot | PowerUpKind::ShieldRecharge => 3,
+            PowerUpKind::ScoreMultiplier | PowerUpKind::Bomb => 2,
+            PowerUpKind::ExtraLife => 1
+        }
+    }
+}
+
+// Where the player destroyed asteroids this tick, filled in by missile::MissileStriker. Inserted
+// into the World as a resource, drop_powerups turns some of them into power-ups.
+#[derive(Default)]
+pub struct Drops {
+    pub positions: Vec<(f64, f64)>
+}
+
+// Called from game::update. Each asteroid destroyed last tick leaves a power-up behind with
+// DROP_CHANCE percent.
+pub fn drop_powerups(ecs: &mut World) {
+    let positions = std::mem::take(&mut ecs.write_resource::<Drops>().positions);
+
+    for (x, y) in positions {
+        let dropped = {
+            let mut game_rng = ecs.write_resource::<GameRng>();
+            let rng = &mut game_rng.rng;
+            if rng.gen_range(0..100) < DROP_CHANCE {
+                let total: u32 = PowerUpKind::ALL.iter().map(|kind| kind.weight()).sum();
+                let mut roll = rng.gen_range(0..total);
+                let kind = PowerUpKind::ALL.into_iter()
+                    .find(|kind| {
+                        if roll < kind.weight() {
+                            return true;
+                        }
+                        roll -= kind.weight();
+                        false
+                    })
+                    .unwrap_or(PowerUpKind::RapidFire);
+                Some((kind, rng.gen_range(0.0..360.0)))
+            } else {
+                None
+            }
+        };
+
+        if let Some((kind, rot)) = dropped {
+            spawn_powerup(ecs, components::Position{x, y, rot}, kind);
+        }
+    }
+}
+
+// Creates a power-up drifting in the direction of position.rot.
+pub fn spawn_powerup(ecs: &mut World, position: components::Position, kind: PowerUpKind) -> Entity {
+    let radians = position.rot.to_radians();
+    let velocity = Vector2D::new(DRIFT_SPEED * radians.sin(), -DRIFT_SPEED * radians.cos());
+
+    ecs.create_entity()
+        .with(components::Position{rot: 0.0, ..position})
+        .with(components::Renderable{
+            tex_name: kind.texture().to_string(),
+            i_w: POWERUP_SIZE,
+            i_h: POWERUP_SIZE,
+            o_w: POWERUP_SIZE,
+            o_h: POWERUP_SIZE,
+            frame: 0,
+            total_frames: 1,
+            rot: 0.0
+        })
+        .with(components::Velocity(velocity))
+        .with(components::BoundaryBehavior::Wrap)
+        .with(components::Collider{shape: Shape::Circle{radius: POWERUP_SIZE as f64 / 2.0}})
+        .with(components::Lifetime{ticks: Some(POWERUP_LIFETIME), distance: None})
+        .with(components::PowerUp{kind})
+        .build()
+}
+
+// What the score the player makes is multiplied by right now, given every ship's effects.
+pub fn score_multiplier<'e>(mut effects: impl Iterator<Item = &'e components::ActiveEffects>) -> u32 {
+    if effects.any(|effects| effects.score_multiplier > 0) {
+        SCORE_MULTIPLIER
+    } else {
+        1
+    }
+}
+
+// Counts down the timed power-ups, see components::ActiveEffects.
+pub struct EffectTimer;
+
+impl<'a> System<'a> for EffectTimer {
+    type SystemData = WriteStorage<'a, components::ActiveEffects>;
+
+    fn run(&mut self, mut effects: Self::SystemData) {
+        for effects in (&mut effects).join() {
+            effects.rapid_fire = effects.rapid_fire.saturating_sub(1);
+            effects.triple_shot = effects.triple_shot.saturating_sub(1);
+            effects.score_multiplier = effects.score_multiplier.saturating_sub(1);
+        }
+    }
+}
+
+// Ships pick up the power-ups they touch. Timed ones start or restart their effect, the others
+// take effect right away.
+pub struct PowerUpCollector;
+
+impl<'a> System<'a> for PowerUpCollector {
+    type SystemData = (
+        ReadStorage<'a, components::Position>,
+        ReadStorage<'a, components::Renderable>,
+        ReadStorage<'a, components::Collider>,
+        ReadStorage<'a, components::PowerUp>,
+        WriteStorage<'a, components::Player>,
+        WriteStorage<'a, components::ActiveEffects>,
+        WriteStorage<'a, components::GameData>,
+        Entities<'a>
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (positions, rends, colliders, powerups, mut players, mut effects, mut gamedatas, entities) = data;
+        let mut extra_lives: u32 = 0;
+
+        for (player_pos, player_rend, player, player_entity) in (&positions, &rends, &mut players, &entities).join() {
+            let player_fallback = Shape::Circle{radius: player_rend.o_w as f64 / 2.0};
+            let player_shape = colliders.get(player_entity).map_or(&player_fallback, |collider| &collider.shape);
+            let player_center = Vector2D::new(player_pos.x, player_pos.y);
+
+            for (pos, rend, powerup, entity) in (&positions, &rends, &powerups, &entities).join() {
+                let fallback = Shape::Circle{radius: rend.o_w as f64 / 2.0};
+                let shape = colliders.get(entity).map_or(&fallback, |collider| &collider.shape);
+                if !collision::shapes_overlap(player_shape, player_center, player_rend.rot, shape, Vector2D::new(pos.x, pos.y), rend.rot) {
+                    continue;
+                }
+                entities.delete(entity).ok();
+
+                let active = effects.get_mut(player_entity);
+                match (powerup.kind, active) {
+                    (PowerUpKind::RapidFire, Some(active)) => active.rapid_fire = EFFECT_TICKS,
+                    (PowerUpKind::TripleShot, Some(active)) => active.triple_shot = EFFECT_TICKS,
+                    (PowerUpKind::ScoreMultiplier, Some(active)) => active.score_multiplier = EFFECT_TICKS,
+                    (PowerUpKind::ShieldRecharge, _) => player.shield = game::SHIELD_ENERGY,
+                    (PowerUpKind::ExtraLife, _) => extra_lives += 1,
+                    (PowerUpKind::Bomb, _) => player.bombs = (player.bombs + 1).min(bomb::MAX_BOMBS),
+                    _ => {}
+                }
+            }
+        }
+
+        for gamedata in (&mut gamedatas).join() {
+            gamedata.lives += extra_lives;
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use std::fs;
+use std::path::Path;
+
+use crate::components::BoundaryBehavior;
+use crate::input::{Action, InputEvent, InputState};
+use crate::resources::Rules;
+
+// A single input change, applied right before the given tick is simulated.
+pub struct ScriptedInput {
+    pub tick: u32,
+    pub event: InputEvent
+}
+
+// Everything needed to reproduce a session: the RNG seed, the rules and the action changes per tick.
+//
+// Replay files are plain text. An optional header sets the seed, length and rules, followed by
+// one action change per line in the form "<tick> <down|up> <action>", e.g. "120 down Thrust",
+// or "<tick> axis <action> <value>" for analog controller input, e.g. "130 axis Thrust 0.5".
+// Actions are recorded instead of keys so replays don't depend on the key bindings.
+// Empty lines and lines starting with '#' are ignored, so hand written input scripts
+// without a header are valid replays too.
+//
+//     seed 1234
+//     ticks 3600
+//     asteroid-collisions off
+//     ship-edges wrap
+//     asteroid-edges bounce
+//     missile-edges despawn
+//     0 down Thrust
+//     45 up Thrust
+//     60 axis RotateLeft 0.25
+pub struct Replay {
+    pub seed: Option<u64>,
+    pub ticks: Option<u32>,
+    // Rules the session was played with, rules missing from the header keep their defaults
+    pub rules: Option<Rules>,
+    pub inputs: Vec<ScriptedInput>
+}
+
+impl Replay {
+    pub fn parse(text: &str) -> Result<Replay, String> {
+        let mut replay = Replay{seed: None, ticks: None, rules: None, inputs: Vec::new()};
+
+        for (line_number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.split_whitespace();
+            let first = parts.next().unwrap_or_default();
+            let second = parts.next().unwrap_or_default();
+
+            match first {
+                "seed" => {
+                    let seed = second.parse::<u64>()
+                        .map_err(|e| format!("line {}: invalid seed \"{}\": {}", line_number + 1, second, e))?;
+                    replay.seed = Some(seed);
+                    continue;
+                },
+                "ticks" => {
+                    let ticks = second.parse::<u32>()
+                        .map_err(|e| format!("line {}: invalid tick count \"{}\": {}", line_number + 1, second, e))?;
+                    replay.ticks = Some(ticks);
+                    continue;
+                },
+                "asteroid-collisions" => {
+                    let enabled = match second {
+                        "on" => true,
+                        "off" => false,
+                        other => return Err(format!("line {}: expected on or off, got \"{}\"", line_number + 1, other))
+                    };
+                    replay.rules.get_or_insert_with(Rules::default).asteroid_collisions = enabled;
+                    continue;
+                },
+                "ship-edges" | "asteroid-edges" | "missile-edges" => {
+                    let edges = BoundaryBehavior::from_name(second)
+                        .ok_or(format!("line {}: invalid edge behavior \"{}\", expected wrap, bounce, despawn or clamp", line_number + 1, second))?;
+                    let rules = replay.rules.get_or_insert_with(Rules::default);
+                    match first {
+                        "ship-edges" => rules.ship_edges = edges,
+                        "asteroid-edges" => rules.asteroid_edges = edges,
+                        _ => rules.missile_edges = edges
+                    }
+                    continue;
+                },
+                _ => {}
+            }
+
+            let tick = first.parse::<u32>()
+                .map_err(|e| format!("line {}: invalid tick \"{}\": {}", line_number + 1, first, e))?;
+
+            let name = parts.next().unwrap_or_default();
+            let action = Action::from_name(name)
+                .ok_or(format!("line {}: unknown action \"{}\"", line_number + 1, name))?;
+
+            let event = match second {
+                "down" => InputEvent::Press(action),
+                "up" => InputEvent::Release(action),
+                "axis" => {
+                    let value = parts.next().unwrap_or_default();
+                    let value = value.parse::<f64>()
+                        .map_err(|e| format!("line {}: invalid axis value \"{}\": {}", line_number + 1, value, e))?;
+                    InputEvent::Axis(action, value)
+                },
+                other => return Err(format!("line {}: expected down, up or axis, got \"{}\"", line_number + 1, other))
+            };
+
+            replay.inputs.push(ScriptedInput{tick, event});
+        }
+
+        // Stable sort so inputs on the same tick keep their file order.
+        replay.inputs.sort_by_key(|input| input.tick);
+        Ok(replay)
+    }
+
+    pub fn load(path: &Path) -> Result<Replay, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Replay::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let mut text = String::new();
+        if let Some(seed) = self.seed {
+            text += &format!("seed {}\n", seed);
+        }
+        if let Some(ticks) = self.ticks {
+            text += &format!("ticks {}\n", ticks);
+        }
+        if let Some(rules) = self.rules {
+            text += &format!("asteroid-collisions {}\n", if rules.asteroid_collisions { "on" } else { "off" });
+            text += &format!("ship-edges {}\n", rules.ship_edges.name());
+            text += &format!("asteroid-edges {}\n", rules.asteroid_edges.name());
+            text += &format!("missile-edges {}\n", rules.missile_edges.name());
+        }
+
+        for input in &self.inputs {
+            text += &match input.event {
+                InputEvent::Press(action) => format!("{} down {}\n", input.tick, action.name()),
+                InputEvent::Release(action) => format!("{} up {}\n", input.tick, action.name()),
+                InputEvent::Axis(action, value) => format!("{} axis {} {}\n", input.tick, action.name(), value)
+            };
+        }
+
+        fs::write(path, text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+// Records the input state once per tick, storing only the actions that changed during the tick.
+pub struct Recorder {
+    replay: Replay,
+    tick: u32
+}
+
+impl Recorder {
+    pub fn new(seed: u64, rules: Rules) -> Self {
+        Recorder {
+            replay: Replay{seed: Some(seed), ticks: None, rules: Some(rules), inputs: Vec::new()},
+            tick: 0
+        }
+    }
+
+    // Must be called every tick with the input state that is about to be simulated.
+    pub fn record(&mut self, input: &InputState) {
+        for event in input.events() {
+            self.replay.inputs.push(ScriptedInput{tick: self.tick, event: *event});
+        }
+
+        self.tick += 1;
+    }
+
+    pub fn save(&mut self, path: &Path) -> Result<(), String> {
+        self.replay.ticks = Some(self.tick);
+        self.replay.save(path)
+    }
+}
+
+// Feeds a replay back into an input state, one tick at a time.
+pub struct Playback {
+    replay: Replay,
+    next_input: usize,
+    tick: u32
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        Playback {
+            replay,
+            next_input: 0,
+            tick: 0
+        }
+    }
+
+    // Applies the action changes for the next tick. Call once per tick right before game::step.
+    pub fn apply(&mut self, input_state: &mut InputState) {
+        let inputs = &self.replay.inputs;
+        while self.next_input < inputs.len() && inputs[self.next_input].tick <= self.tick {
+            input_state.apply(inputs[self.next_input].event);
+            self.next_input += 1;
+        }
+
+        self.tick += 1;
+    }
+
+    // True once the recorded length has been played back. A replay without a length, like a hand
+    // written input script, never finishes, the game just runs on after its last input.
+    pub fn finished(&self) -> bool {
+        self.replay.ticks.is_some_and(|ticks| self.tick >= ticks)
+    }
+}
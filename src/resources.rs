@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use vector2d::Vector2D;
+
+use crate::components::BoundaryBehavior;
+
+// Size of the playing field in pixels, the window and headless runs both use it.
+pub const ARENA_WIDTH: u32 = 1280;
+pub const ARENA_HEIGHT: u32 = 640;
+
+// Size of the playing field in pixels. Inserted into the World as a resource
+// so the simulation doesn't depend on the window it's rendered in.
+pub struct Arena {
+    pub width: u32,
+    pub height: u32
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Arena {
+            width: ARENA_WIDTH,
+            height: ARENA_HEIGHT
+        }
+    }
+}
+
+// Convex hulls of the sprites keyed by texture name, as made by collision::hull_from_alpha.
+// Filled in by the front end when it loads the images, entities whose sprite has no hull
+// get a simple shape instead.
+#[derive(Default)]
+pub struct Hulls {
+    pub shapes: HashMap<String, Vec<Vector2D<f64>>>
+}
+
+// Optional game rules, chosen before the game starts.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Rules {
+    // Asteroids bounce off each other instead of passing through
+    pub asteroid_collisions: bool,
+    // What the ship, asteroids and missiles do at the edge of the arena
+    pub ship_edges: BoundaryBehavior,
+    pub asteroid_edges: BoundaryBehavior,
+    pub missile_edges: BoundaryBehavior
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            asteroid_collisions: false,
+            ship_edges: BoundaryBehavior::Wrap,
+            asteroid_edges: BoundaryBehavior::Bounce,
+            missile_edges: BoundaryBehavior::Despawn
+        }
+    }
+}
+
+// The one source of randomness for the simulation. Everything that spawns or
+// randomizes entities must draw from this, so a run can be replayed from its seed.
+pub struct GameRng {
+    pub seed: u64,
+    pub rng: StdRng
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        GameRng {
+            seed,
+            rng: StdRng::seed_from_u64(seed)
+        }
+    }
+}
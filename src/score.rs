@@ -0,0 +1,108 @@
+use specs::prelude::*;
+use specs::{Entities, Join};
+use vector2d::Vector2D;
+
+use crate::components;
+use crate::powerup;
+
+// Points for destroying an asteroid, by how big it is. Like the original, the small ones are
+// hardest to hit and worth the most.
+pub const LARGE_ASTEROID_POINTS: u32 = 20;
+pub const MEDIUM_ASTEROID_POINTS: u32 = 50;
+pub const SMALL_ASTEROID_POINTS: u32 = 100;
+// Asteroids at least this many pixels across count as large or medium.
+const LARGE_ASTEROID_SIZE: u32 = 96;
+const MEDIUM_ASTEROID_SIZE: u32 = 48;
+// Ticks the ship's shots have to land the next hit in to keep the combo going.
+pub const COMBO_WINDOW: u32 = 120;
+// Every this many hits in a row the combo multiplier goes up by one, up to MAX_COMBO.
+pub const HITS_PER_COMBO_STEP: u32 = 3;
+pub const MAX_COMBO: u32 = 5;
+// Ticks a score popup floats up for, and the pixels per tick it rises.
+pub const POPUP_TICKS: u32 = 45;
+const POPUP_RISE: f64 = 1.0;
+
+pub fn asteroid_points(size: u32) -> u32 {
+    if size >= LARGE_ASTEROID_SIZE {
+        LARGE_ASTEROID_POINTS
+    } else if size >= MEDIUM_ASTEROID_SIZE {
+        MEDIUM_ASTEROID_POINTS
+    } else {
+        SMALL_ASTEROID_POINTS
+    }
+}
+
+// What a combo of this many hits in a row multiplies the score by.
+pub fn combo_multiplier(hits: u32) -> u32 {
+    (1 + hits.saturating_sub(1) / HITS_PER_COMBO_STEP).min(MAX_COMBO)
+}
+
+// What the player's shots did this tick. Inserted into the World as a resource, filled in by
+// missile::MissileStriker, ufo::UfoHitter and the systems in movement that delete missiles, then
+// emptied by ScoreKeeper.
+#[derive(Default)]
+pub struct Scoring {
+    // Missiles that hit something, once for every asteroid or UFO they hit
+    pub hits: Vec<Entity>,
+    // Missiles that ran out of time or range, or left the arena
+    pub misses: Vec<Entity>,
+    // Where the player destroyed something and the points it's worth before any multiplier
+    pub kills: Vec<(f64, f64, u32)>
+}
+
+// Runs the ships' combos and adds this tick's points to the score, showing a popup where each
+// of them was made. A shot that ends without hitting anything breaks the combo, and so does
+// going COMBO_WINDOW ticks without a hit. The combo is lost with the ship.
+pub struct ScoreKeeper;
+
+impl<'a> System<'a> for ScoreKeeper {
+    type SystemData = (
+        WriteExpect<'a, Scoring>,
+        WriteStorage<'a, components::Combo>,
+        ReadStorage<'a, components::ActiveEffects>,
+        WriteStorage<'a, components::GameData>,
+        WriteStorage<'a, components::Position>,
+        WriteStorage<'a, components::Velocity>,
+        WriteStorage<'a, components::Lifetime>,
+        WriteStorage<'a, components::ScorePopup>,
+        Entities<'a>
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut scoring, mut combos, effects, mut gamedatas, mut positions, mut velocities, mut lifetimes, mut popups, entities) = data;
+        let scoring = std::mem::take(&mut *scoring);
+
+        // A missile that hit on its way out of the arena or at the end of its range didn't miss.
+        let missed = scoring.misses.iter().any(|missile| !scoring.hits.contains(missile));
+
+        let mut combo_hits: u32 = 0;
+        for combo in (&mut combos).join() {
+            combo.ticks = combo.ticks.saturating_sub(1);
+            if missed || combo.ticks == 0 {
+                combo.hits = 0;
+            }
+            if !scoring.hits.is_empty() {
+                combo.hits += scoring.hits.len() as u32;
+                combo.ticks = COMBO_WINDOW;
+            }
+            combo_hits = combo_hits.max(combo.hits);
+        }
+
+        let multiplier = combo_multiplier(combo_hits) * powerup::score_multiplier((&effects).join());
+        let mut score: u32 = 0;
+        for (x, y, points) in scoring.kills {
+            let points = points * multiplier;
+            score += points;
+
+            let popup = entities.create();
+            positions.insert(popup, components::Position{x, y, rot: 0.0}).ok();
+            velocities.insert(popup, components::Velocity(Vector2D::new(0.0, -POPUP_RISE))).ok();
+            lifetimes.insert(popup, components::Lifetime{ticks: Some(POPUP_TICKS), distance: None}).ok();
+            popups.insert(popup, components::ScorePopup{points}).ok();
+        }
+
+        for gamedata in (&mut gamedatas).join() {
+            gamedata.score += score;
+        }
+    }
+}
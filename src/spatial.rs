@@ -0,0 +1,151 @@
+use specs::{System, ReadStorage, ReadExpect, WriteExpect, Join, LendJoin, Entities, Entity};
+
+use crate::components;
+use crate::resources::Arena;
+
+// Side of a grid cell in pixels, the size of the largest asteroid.
+pub const CELL_SIZE: f64 = 128.0;
+
+// An asteroid in the grid. Its radius is the bounding radius of its collider.
+#[derive(Clone, Copy)]
+pub struct GridEntry {
+    pub entity: Entity,
+    pub x: f64,
+    pub y: f64,
+    pub radius: f64,
+    // Distance moved in the last tick
+    pub step: f64
+}
+
+// Broad-phase for collision checks. Every asteroid is put in the cell its center is in, so a
+// query only has to look at the cells around a point instead of at every asteroid.
+// Rebuilt once per tick by SpatialGridBuilder, after the asteroids have moved.
+pub struct SpatialGrid {
+    cell_size: f64,
+    columns: i32,
+    rows: i32,
+    cells: Vec<Vec<GridEntry>>,
+    // Largest radius in the grid, how far an entry can reach out of its cell.
+    max_radius: f64,
+    // Largest distance moved in the last tick, how far an entry can have come from for swept tests.
+    max_step: f64
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f64) -> Self {
+        SpatialGrid {
+            cell_size,
+            columns: 0,
+            rows: 0,
+            cells: Vec::new(),
+            max_radius: 0.0,
+            max_step: 0.0
+        }
+    }
+
+    // Empties the grid and resizes it to cover the arena. The cells keep their allocations.
+    pub fn clear(&mut self, arena: &Arena) {
+        self.columns = ((arena.width as f64 / self.cell_size).ceil() as i32).max(1);
+        self.rows = ((arena.height as f64 / self.cell_size).ceil() as i32).max(1);
+        self.cells.resize_with((self.columns * self.rows) as usize, Vec::new);
+        for cell in &mut self.cells {
+            cell.clear();
+        }
+        self.max_radius = 0.0;
+        self.max_step = 0.0;
+    }
+
+    pub fn insert(&mut self, entry: GridEntry) {
+        let column = self.column(entry.x);
+        let row = self.row(entry.y);
+        self.cells[(row * self.columns + column) as usize].push(entry);
+        self.max_radius = self.max_radius.max(entry.radius);
+        self.max_step = self.max_step.max(entry.step);
+    }
+
+    // Calls found for every entry whose circle overlaps the given one.
+    pub fn query<F: FnMut(&GridEntry)>(&self, x: f64, y: f64, radius: f64, mut found: F) {
+        if self.cells.is_empty() {
+            return;
+        }
+
+        // Entities outside the arena are kept in the border cells, clamping the range keeps them in reach.
+        let reach = radius + self.max_radius;
+        let (first_column, last_column) = (self.column(x - reach), self.column(x + reach));
+        let (first_row, last_row) = (self.row(y - reach), self.row(y + reach));
+
+        for row in first_row..=last_row {
+            for column in first_column..=last_column {
+                for entry in &self.cells[(row * self.columns + column) as usize] {
+                    let diff_x = x - entry.x;
+                    let diff_y = y - entry.y;
+                    let reach = radius + entry.radius;
+                    if (diff_x*diff_x) + (diff_y*diff_y) < reach*reach {
+                        found(entry);
+                    }
+                }
+            }
+        }
+    }
+
+    // Calls found for every entry that may have touched a circle moving from start to end during
+    // the last tick, with both the point and the entries moving. Only a broad-phase, the caller
+    // does the exact test with collision::segment_hits_circle.
+    pub fn query_path<F: FnMut(&GridEntry)>(&self, start: (f64, f64), end: (f64, f64), radius: f64, mut found: F) {
+        if self.cells.is_empty() {
+            return;
+        }
+
+        let reach = radius + self.max_radius + self.max_step;
+        let (first_column, last_column) = (self.column(start.0.min(end.0) - reach), self.column(start.0.max(end.0) + reach));
+        let (first_row, last_row) = (self.row(start.1.min(end.1) - reach), self.row(start.1.max(end.1) + reach));
+
+        for row in first_row..=last_row {
+            for column in first_column..=last_column {
+                for entry in &self.cells[(row * self.columns + column) as usize] {
+                    found(entry);
+                }
+            }
+        }
+    }
+
+    fn column(&self, x: f64) -> i32 {
+        ((x / self.cell_size).floor() as i32).clamp(0, self.columns - 1)
+    }
+
+    fn row(&self, y: f64) -> i32 {
+        ((y / self.cell_size).floor() as i32).clamp(0, self.rows - 1)
+    }
+}
+
+impl Default for SpatialGrid {
+    fn default() -> Self {
+        SpatialGrid::new(CELL_SIZE)
+    }
+}
+
+pub struct SpatialGridBuilder;
+
+impl<'a> System<'a> for SpatialGridBuilder {
+    type SystemData = (
+        ReadStorage<'a, components::Position>,
+        ReadStorage<'a, components::Renderable>,
+        ReadStorage<'a, components::Asteroid>,
+        ReadStorage<'a, components::PreviousPosition>,
+        ReadStorage<'a, components::Collider>,
+        ReadExpect<'a, Arena>,
+        WriteExpect<'a, SpatialGrid>,
+        Entities<'a>
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (positions, rends, asteroids, previous_positions, colliders, arena, mut grid, entities) = data;
+
+        grid.clear(&arena);
+        for (pos, rend, _, previous, collider, entity) in (&positions, &rends, &asteroids, (&previous_positions).maybe(), (&colliders).maybe(), &entities).join() {
+            let step = previous.map_or(0.0, |previous| ((pos.x - previous.x).powi(2) + (pos.y - previous.y).powi(2)).sqrt());
+            let radius = collider.map_or(rend.o_w as f64 / 2.0, |collider| collider.shape.bounding_radius());
+            grid.insert(GridEntry{entity, x: pos.x, y: pos.y, radius, step});
+        }
+    }
+}
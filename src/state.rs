@@ -0,0 +1,74 @@
+// Which screen the game is on. Only Playing advances the simulation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GameState {
+    Title,
+    Playing,
+    Paused,
+    GameOver,
+    // Entering initials for a new high score, after GameOver
+    EnterName,
+    Settings
+}
+
+// Tracks the current state and refuses transitions that make no sense, e.g. pausing the title screen.
+pub struct StateMachine {
+    current: GameState,
+    // Settings can be opened from several states and returns to the one it was opened from.
+    settings_return: GameState
+}
+
+impl StateMachine {
+    pub fn new() -> Self {
+        StateMachine {
+            current: GameState::Title,
+            settings_return: GameState::Title
+        }
+    }
+
+    pub fn current(&self) -> GameState {
+        self.current
+    }
+
+    pub fn runs_simulation(&self) -> bool {
+        self.current == GameState::Playing
+    }
+
+    pub fn can_transition(&self, to: GameState) -> bool {
+        use GameState::*;
+
+        match (self.current, to) {
+            (Title, Playing) | (Title, Settings) => true,
+            (Playing, Paused) | (Playing, GameOver) => true,
+            // A replay that has been played back to its end
+            (Playing, Title) => true,
+            (Paused, Playing) | (Paused, Settings) | (Paused, Title) => true,
+            (GameOver, Title) | (GameOver, EnterName) => true,
+            (EnterName, Title) => true,
+            (Settings, to) => to == self.settings_return,
+            _ => false
+        }
+    }
+
+    // Moves to another state if the transition is allowed, returns whether it happened.
+    pub fn transition(&mut self, to: GameState) -> bool {
+        if !self.can_transition(to) {
+            return false;
+        }
+
+        if to == GameState::Settings {
+            self.settings_return = self.current;
+        }
+        self.current = to;
+        true
+    }
+
+    pub fn close_settings(&mut self) -> bool {
+        self.transition(self.settings_return)
+    }
+}
+
+impl Default for StateMachine {
+    fn default() -> Self {
+        StateMachine::new()
+    }
+}
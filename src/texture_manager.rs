@@ -60,4 +60,4 @@ impl<'l, T> ResourceLoader<'l, Texture<'l>> for TextureCreator<T> {
         // println!("LOADED A TEXTURE");
         self.load_texture(path)
     }
-}
\ No newline at end of file
+}
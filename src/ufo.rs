@@ -0,0 +1,272 @@
+use rand::Rng;
+use specs::prelude::*;
+use specs::{Entities, Join};
+use vector2d::Vector2D;
+
+use crate::collision::{self, Shape};
+use crate::components::{self, UfoSize};
+use crate::game;
+use crate::score::Scoring;
+use crate::resources::{Arena, GameRng};
+use crate::weapons::Weapon;
+
+// Ticks between UFOs at level 1. Each level takes UFO_INTERVAL_STEP off, down to UFO_MIN_INTERVAL.
+pub const UFO_INTERVAL: u32 = 1200;
+const UFO_INTERVAL_STEP: u32 = 60;
+const UFO_MIN_INTERVAL: u32 = 420;
+// Percent chance of a small UFO per level after the first, up to UFO_SMALL_MAX_CHANCE.
+const UFO_SMALL_CHANCE_STEP: u32 = 15;
+const UFO_SMALL_MAX_CHANCE: u32 = 80;
+// Ticks between changes of the vertical direction, picked at random in this range.
+const ZIGZAG_TICKS: std::ops::RangeInclusive<u32> = 45..=90;
+// Part of the horizontal speed a UFO moves up or down at while zigzagging.
+const ZIGZAG_SPEED: f64 = 0.6;
+// Degrees a small UFO's aim is off at most, a level takes one off down to SMALL_MIN_AIM_ERROR.
+const SMALL_AIM_ERROR: f64 = 12.0;
+const SMALL_MIN_AIM_ERROR: f64 = 2.0;
+
+impl UfoSize {
+    // Size of the sprite on screen
+    pub fn width(self) -> u32 {
+        match self {
+            UfoSize::Large => 64,
+            UfoSize::Small => 32
+        }
+    }
+
+    // Pixels per tick across the arena
+    pub fn speed(self) -> f64 {
+        match self {
+            UfoSize::Large => 2.0,
+            UfoSize::Small => 3.0
+        }
+    }
+
+    // Ticks between shots
+    pub fn fire_interval(self) -> u32 {
+        match self {
+            UfoSize::Large => 90,
+            UfoSize::Small => 60
+        }
+    }
+
+    // Score for shooting it down
+    pub fn score(self) -> u32 {
+        match self {
+            UfoSize::Large => 200,
+            UfoSize::Small => 1000
+        }
+    }
+}
+
+// What a UFO shoots, it never runs out.
+pub fn ufo_gun() -> Weapon {
+    Weapon {
+        name: String::from("ufo"),
+        texture: String::from("img/ufo_shot.png"),
+        texture_width: 8,
+        texture_height: 8,
+        width: 8,
+        height: 8,
+        speed: 4.0,
+        cooldown: 0,
+        max_active: None,
+        lifetime: None,
+        range: Some(600.0),
+        ..Weapon::default()
+    }
+}
+
+// Creates a UFO at position flying horizontally, right if position.rot is 90 and left otherwise.
+// It's deleted once it leaves the arena.
+pub fn spawn_ufo(ecs: &mut World, position: components::Position, size: UfoSize) -> Entity {
+    let width = size.width();
+    let height = width / 2;
+    let direction = if position.rot == 90.0 { 1.0 } else { -1.0 };
+    let collider = game::collider_for(ecs, "img/ufo.png", width, height,
+                                      Shape::Box{half_width: width as f64 / 2.0, half_height: height as f64 / 2.0});
+
+    ecs.create_entity()
+        .with(components::PreviousPosition{x: position.x, y: position.y})
+        .with(components::Position{rot: 0.0, ..position})
+        .with(components::Renderable{
+            tex_name: String::from("img/ufo.png"),
+            i_w: 64,
+            i_h: 32,
+            o_w: width,
+            o_h: height,
+            frame: 0,
+            total_frames: 1,
+            rot: 0.0
+        })
+        .with(components::Velocity(Vector2D::new(direction * size.speed(), 0.0)))
+        .with(components::BoundaryBehavior::Despawn)
+        .with(collider)
+        .with(components::Ufo{
+            size,
+            turn_timer: 0,
+            fire_timer: size.fire_interval()
+        })
+        .build()
+}
+
+// Fires a UFO shot in the direction of position.rot.
+fn fire_shot(ecs: &mut World, position: components::Position) {
+    let shot = game::spawn_missile(ecs, position, &ufo_gun());
+    if let Some(missile) = ecs.write_storage::<components::Missile>().get_mut(shot) {
+        missile.hostile = true;
+    }
+}
+
+// Called from game::update. Sends in a new UFO once the timer runs out, makes the ones flying
+// zigzag and shoot: large ones anywhere, small ones at the ship with an aim that gets better
+// with the level.
+pub fn update_ufos(ecs: &mut World, player: Option<(f64, f64)>) {
+    let ufo_count = ecs.read_storage::<components::Ufo>().join().count();
+
+    let mut spawn_level = None;
+    if ufo_count == 0 && player.is_some() {
+        let mut gamedatas = ecs.write_storage::<components::GameData>();
+        for gamedata in (&mut gamedatas).join() {
+            gamedata.ufo_timer = gamedata.ufo_timer.saturating_sub(1);
+            if gamedata.ufo_timer == 0 {
+                let step = UFO_INTERVAL_STEP.saturating_mul(gamedata.level);
+                gamedata.ufo_timer = UFO_INTERVAL.saturating_sub(step).max(UFO_MIN_INTERVAL);
+                spawn_level = Some(gamedata.level);
+            }
+        }
+    }
+
+    let (arena_width, arena_height) = {
+        let arena = ecs.read_resource::<Arena>();
+        (arena.width as f64, arena.height as f64)
+    };
+
+    if let Some(level) = spawn_level {
+        let (size, position) = {
+            let mut game_rng = ecs.write_resource::<GameRng>();
+            let rng = &mut game_rng.rng;
+            let small_chance = (level.saturating_sub(1) * UFO_SMALL_CHANCE_STEP).min(UFO_SMALL_MAX_CHANCE);
+            let size = if rng.gen_range(0..100) < small_chance { UfoSize::Small } else { UfoSize::Large };
+            // Comes in on the left or right edge, away from the top and bottom.
+            let margin = size.width() as f64;
+            let y = rng.gen_range(margin..(arena_height - margin));
+            let position = if rng.gen_bool(0.5) {
+                components::Position{x: 0.0, y, rot: 90.0}
+            } else {
+                components::Position{x: arena_width, y, rot: 270.0}
+            };
+            (size, position)
+        };
+        spawn_ufo(ecs, position, size);
+    }
+
+    let level = {
+        let gamedatas = ecs.read_storage::<components::GameData>();
+        gamedatas.join().map(|gamedata| gamedata.level).next().unwrap_or(1)
+    };
+
+    let mut shots = Vec::<components::Position>::new();
+    {
+        let positions = ecs.read_storage::<components::Position>();
+        let mut velocities = ecs.write_storage::<components::Velocity>();
+        let mut ufos = ecs.write_storage::<components::Ufo>();
+        let mut game_rng = ecs.write_resource::<GameRng>();
+        let rng = &mut game_rng.rng;
+
+        for (pos, velocity, ufo) in (&positions, &mut velocities, &mut ufos).join() {
+            let margin = ufo.size.width() as f64;
+            let vertical_speed = ufo.size.speed() * ZIGZAG_SPEED;
+
+            ufo.turn_timer = ufo.turn_timer.saturating_sub(1);
+            if ufo.turn_timer == 0 {
+                ufo.turn_timer = rng.gen_range(ZIGZAG_TICKS);
+                velocity.0.y = vertical_speed * rng.gen_range(-1..=1) as f64;
+            }
+            // Turns back before it gets close to the top or bottom, where it would be deleted.
+            if pos.y < margin {
+                velocity.0.y = vertical_speed;
+            }
+            if pos.y > arena_height - margin {
+                velocity.0.y = -vertical_speed;
+            }
+
+            ufo.fire_timer = ufo.fire_timer.saturating_sub(1);
+            if ufo.fire_timer > 0 {
+                continue;
+            }
+            ufo.fire_timer = ufo.size.fire_interval();
+
+            let rot = match (ufo.size, player) {
+                (UfoSize::Small, Some((player_x, player_y))) => {
+                    let aim = (player_x - pos.x).atan2(pos.y - player_y).to_degrees();
+                    let error = (SMALL_AIM_ERROR - level as f64).max(SMALL_MIN_AIM_ERROR);
+                    aim + rng.gen_range(-error..=error)
+                },
+                _ => rng.gen_range(0.0..360.0)
+            };
+            shots.push(components::Position{x: pos.x, y: pos.y, rot});
+        }
+    }
+
+    for shot in shots {
+        fire_shot(ecs, shot);
+    }
+}
+
+// Player missiles against UFOs. Like missile::MissileStriker the whole path the missile took
+// during the tick is checked, against a circle as tall as the UFO, and the missile's collider
+// where it ended up. A UFO goes down to a single hit.
+pub struct UfoHitter;
+
+impl<'a> System<'a> for UfoHitter {
+    type SystemData = (
+        ReadStorage<'a, components::Position>,
+        ReadStorage<'a, components::PreviousPosition>,
+        ReadStorage<'a, components::Renderable>,
+        ReadStorage<'a, components::Collider>,
+        ReadStorage<'a, components::Missile>,
+        ReadStorage<'a, components::Ufo>,
+        WriteExpect<'a, Scoring>,
+        Entities<'a>
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (positions, previous_positions, rends, colliders, missiles, ufos, mut scoring, entities) = data;
+
+        for (ufo_pos, ufo_rend, ufo, ufo_entity) in (&positions, &rends, &ufos, &entities).join() {
+            let ufo_fallback = Shape::Circle{radius: ufo_rend.o_w as f64 / 2.0};
+            let ufo_shape = colliders.get(ufo_entity).map_or(&ufo_fallback, |collider| &collider.shape);
+            let ufo_end = Vector2D::new(ufo_pos.x, ufo_pos.y);
+            let ufo_start = previous_positions.get(ufo_entity).map_or(ufo_end, |previous| Vector2D::new(previous.x, previous.y));
+
+            for (missile_pos, missile_rend, missile, missile_entity) in (&positions, &rends, &missiles, &entities).join() {
+                // A missile that already hit an asteroid or another UFO this tick, or ran out of range,
+                // is only deleted once the tick is over and mustn't score again.
+                if missile.hostile || scoring.misses.contains(&missile_entity)
+                    || (!missile.piercing && scoring.hits.contains(&missile_entity)) {
+                    continue;
+                }
+
+                let missile_end = Vector2D::new(missile_pos.x, missile_pos.y);
+                let missile_start = previous_positions.get(missile_entity).map_or(missile_end, |previous| Vector2D::new(previous.x, previous.y));
+                let mut hit = collision::segment_hits_circle(missile_start - ufo_start, missile_end - ufo_end, ufo_rend.o_h as f64 / 2.0).is_some();
+                if !hit {
+                    if let Some(collider) = colliders.get(missile_entity) {
+                        hit = collision::shapes_overlap(&collider.shape, missile_end, missile_rend.rot, ufo_shape, ufo_end, ufo_rend.rot);
+                    }
+                }
+
+                if hit {
+                    if !missile.piercing {
+                        entities.delete(missile_entity).ok();
+                    }
+                    entities.delete(ufo_entity).ok();
+                    scoring.hits.push(missile_entity);
+                    scoring.kills.push((ufo_pos.x, ufo_pos.y, ufo.size.score()));
+                    break;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Texture, TextureCreator};
+use sdl2::ttf::Font;
+use sdl2::video::WindowContext;
+
+pub struct UIElement<'a>{
+    pub texture : Texture<'a>,
+    pub position : Rect
+}
+
+// Renders a line of text at the given height, keeping the font's aspect ratio.
+pub fn text_element<'a>(font: &Font, texture_creator: &'a TextureCreator<WindowContext>, text: &str, color: Color, x: i32, y: i32, height: u32) -> Result<UIElement<'a>, String> {
+    let surface = font
+            .render(text)
+            .blended(color)
+            .map_err(|e| e.to_string())?;
+
+    let width = surface.width() * height / surface.height();
+    let texture : Texture = texture_creator
+            .create_texture_from_surface(surface)
+            .map_err(|e| e.to_string())?;
+
+    Ok(UIElement { texture, position: Rect::new(x, y, width, height) })
+}
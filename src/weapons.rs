@@ -0,0 +1,152 @@
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+// What the ship shoots and how often. One [[weapon]] table in data/weapons.toml:
+//
+//     [[weapon]]
+//     name = "spread"
+//     texture = "img/bullet.png"
+//     texture_width = 8
+//     texture_height = 32
+//     width = 6
+//     height = 24
+//     speed = 5.0
+//     cooldown = 25
+//     shots = 5
+//     spread = 40.0
+//     range = 400.0
+#[derive(Deserialize, Clone, Debug)]
+pub struct Weapon {
+    pub name: String,
+    // Sprite and the size of its source image
+    pub texture: String,
+    pub texture_width: u32,
+    pub texture_height: u32,
+    // Size on screen
+    pub width: u32,
+    pub height: u32,
+    // Pixels per tick
+    pub speed: f64,
+    // Ticks between shots while Fire is held
+    pub cooldown: u32,
+    // Most shots of this weapon in flight at once, no limit if left out
+    pub max_active: Option<usize>,
+    // Ticks a shot lives, no limit if left out
+    pub lifetime: Option<u32>,
+    // Pixels a shot flies before it's gone, no limit if left out
+    pub range: Option<f64>,
+    // Damage a shot does to what it hits
+    #[serde(default = "one")]
+    pub damage: u32,
+    // Shots fired at once, fanned out evenly over spread degrees around the ship's heading
+    #[serde(default = "one")]
+    pub shots: u32,
+    #[serde(default)]
+    pub spread: f64,
+    // Degrees per tick a shot turns towards the nearest asteroid, not homing if left out
+    pub turn_rate: Option<f64>,
+    // Shots fly on through everything they hit instead of stopping at the first
+    #[serde(default)]
+    pub piercing: bool
+}
+
+fn one() -> u32 {
+    1
+}
+
+impl Default for Weapon {
+    fn default() -> Self {
+        Weapon {
+            name: String::from("bullet"),
+            texture: String::from("img/bullet.png"),
+            texture_width: 8,
+            texture_height: 32,
+            width: 8,
+            height: 32,
+            speed: 5.0,
+            cooldown: 10,
+            max_active: Some(5),
+            lifetime: None,
+            range: Some(640.0),
+            damage: 1,
+            shots: 1,
+            spread: 0.0,
+            turn_rate: None,
+            piercing: false
+        }
+    }
+}
+
+// Every weapon from the data file, in the order they're listed. The ship starts with the first one.
+// Inserted into the World as a resource.
+#[derive(Deserialize, Clone, Debug)]
+pub struct Weapons {
+    #[serde(rename = "weapon")]
+    pub list: Vec<Weapon>
+}
+
+impl Default for Weapons {
+    fn default() -> Self {
+        Weapons {
+            list: vec![Weapon::default()]
+        }
+    }
+}
+
+impl Weapons {
+    pub fn parse(text: &str) -> Result<Weapons, String> {
+        let weapons = toml::from_str::<Weapons>(text).map_err(|e| e.to_string())?;
+        weapons.validate()?;
+        Ok(weapons)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.list.is_empty() {
+            return Err("there must be at least one [[weapon]]".to_string());
+        }
+
+        for (i, weapon) in self.list.iter().enumerate() {
+            if self.list[..i].iter().any(|other| other.name == weapon.name) {
+                return Err(format!("weapon \"{}\" is listed twice", weapon.name));
+            }
+            if weapon.speed <= 0.0 {
+                return Err(format!("speed of {} must be above 0", weapon.name));
+            }
+            if weapon.max_active == Some(0) {
+                return Err(format!("max_active of {} must be above 0", weapon.name));
+            }
+            if weapon.lifetime == Some(0) || weapon.range.is_some_and(|range| range <= 0.0) {
+                return Err(format!("lifetime and range of {} must be above 0", weapon.name));
+            }
+            if weapon.damage == 0 || weapon.shots == 0 {
+                return Err(format!("damage and shots of {} must be above 0", weapon.name));
+            }
+            if weapon.turn_rate.is_some_and(|turn_rate| turn_rate <= 0.0) {
+                return Err(format!("turn_rate of {} must be above 0", weapon.name));
+            }
+        }
+        Ok(())
+    }
+
+    // The first weapon, the one the ship starts with.
+    pub fn first(&self) -> &Weapon {
+        &self.list[0]
+    }
+
+    // The weapon at index, or the first one if the list got shorter.
+    pub fn get(&self, index: usize) -> &Weapon {
+        self.list.get(index).unwrap_or(self.first())
+    }
+
+    // A missing file is not an error, the built in bullet is used instead.
+    pub fn load(path: &Path) -> Result<Weapons, String> {
+        if !path.exists() {
+            return Ok(Weapons::default());
+        }
+
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Weapons::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
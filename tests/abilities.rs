@@ -0,0 +1,245 @@
+mod common;
+
+use specs::{World, WorldExt, Join, Entity};
+use vector2d::Vector2D;
+
+use asteroid_game::bomb;
+use asteroid_game::components;
+use asteroid_game::game;
+use asteroid_game::input::{Action, InputState};
+use asteroid_game::score;
+use asteroid_game::weapons::Weapon;
+
+// A ship at (400, 300) and nothing else. In god mode hyperspace jumps can't fail and nothing
+// destroys the ship.
+fn ship_world(seed: u64, god_mode: bool) -> World {
+    common::ship_world(400.0, 300.0, seed, god_mode)
+}
+
+fn asteroid(ecs: &mut World, x: f64, y: f64, size: u32, velocity: Vector2D<f64>) -> Entity {
+    let asteroid = game::create_asteroid(ecs, components::Position{x, y, rot: 0.0}, size);
+    ecs.write_storage::<components::Velocity>().insert(asteroid, components::Velocity(velocity)).unwrap();
+    asteroid
+}
+
+fn run(ecs: &mut World, input: &mut InputState, ticks: u32) {
+    let mut dispatcher = game::build_dispatcher();
+    for _ in 0..ticks {
+        game::step(ecs, &mut dispatcher, input);
+    }
+}
+
+fn ship_position(ecs: &World) -> Option<Vector2D<f64>> {
+    let positions = ecs.read_storage::<components::Position>();
+    let players = ecs.read_storage::<components::Player>();
+    (&positions, &players).join().next().map(|(pos, _)| Vector2D::new(pos.x, pos.y))
+}
+
+#[test]
+fn shield_pushes_an_asteroid_away_instead_of_losing_the_ship() {
+    let mut ecs = ship_world(1, false);
+    let rock = asteroid(&mut ecs, 430.0, 300.0, 32, Vector2D::new(-2.0, 0.0));
+    let mut input = InputState::default();
+
+    input.press(Action::Shield);
+    run(&mut ecs, &mut input, 1);
+
+    assert!(ship_position(&ecs).is_some());
+    let positions = ecs.read_storage::<components::Position>();
+    let velocities = ecs.read_storage::<components::Velocity>();
+    assert!(positions.get(rock).unwrap().x >= 400.0 + game::SHIELD_RADIUS + 16.0);
+    assert!(velocities.get(rock).unwrap().0.x > 0.0);
+}
+
+#[test]
+fn shield_drops_once_its_energy_runs_out_and_recharges() {
+    let mut ecs = ship_world(1, true);
+    asteroid(&mut ecs, 1000.0, 100.0, 32, Vector2D::new(0.0, 0.0));
+    let mut input = InputState::default();
+
+    input.press(Action::Shield);
+    run(&mut ecs, &mut input, 180);
+    assert_eq!(ecs.read_storage::<components::Player>().join().next().unwrap().shield, 0);
+    run(&mut ecs, &mut input, 1);
+    assert!(!ecs.read_storage::<components::Player>().join().next().unwrap().shielded);
+
+    input.release(Action::Shield);
+    run(&mut ecs, &mut input, 59);
+    let players = ecs.read_storage::<components::Player>();
+    assert_eq!(players.join().next().unwrap().shield, 60);
+}
+
+#[test]
+fn empty_shield_stays_down_while_shield_is_held() {
+    let mut ecs = ship_world(1, true);
+    let mut input = InputState::default();
+
+    input.press(Action::Shield);
+    run(&mut ecs, &mut input, 180);
+    for _ in 0..game::SHIELD_MIN_ENERGY {
+        run(&mut ecs, &mut input, 1);
+        assert!(!ecs.read_storage::<components::Player>().join().next().unwrap().shielded);
+    }
+
+    // Recharged enough to come back up.
+    run(&mut ecs, &mut input, 1);
+    assert!(ecs.read_storage::<components::Player>().join().next().unwrap().shielded);
+}
+
+#[test]
+fn empty_shield_comes_back_up_once_shield_is_pressed_again() {
+    let mut ecs = ship_world(1, true);
+    let mut input = InputState::default();
+
+    input.press(Action::Shield);
+    run(&mut ecs, &mut input, 190);
+    input.release(Action::Shield);
+    run(&mut ecs, &mut input, 1);
+    input.press(Action::Shield);
+    run(&mut ecs, &mut input, 1);
+    assert!(ecs.read_storage::<components::Player>().join().next().unwrap().shielded);
+}
+
+#[test]
+fn hyperspace_jumps_away_from_asteroids_and_stops_the_ship() {
+    let mut ecs = ship_world(1, true);
+    asteroid(&mut ecs, 420.0, 300.0, 128, Vector2D::new(0.0, 0.0));
+    let mut input = InputState::default();
+
+    input.press(Action::Hyperspace);
+    run(&mut ecs, &mut input, 1);
+
+    let position = ship_position(&ecs).unwrap();
+    assert!((position - Vector2D::new(420.0, 300.0)).length() > 200.0);
+    let players = ecs.read_storage::<components::Player>();
+    assert_eq!(players.join().next().unwrap().hyperspace_cooldown, game::HYPERSPACE_COOLDOWN);
+}
+
+#[test]
+fn hyperspace_has_to_recharge_between_jumps() {
+    let mut ecs = ship_world(1, true);
+    asteroid(&mut ecs, 1000.0, 100.0, 32, Vector2D::new(0.0, 0.0));
+    let mut input = InputState::default();
+
+    input.press(Action::Hyperspace);
+    run(&mut ecs, &mut input, 1);
+    let after_jump = ship_position(&ecs).unwrap();
+    input.release(Action::Hyperspace);
+    input.press(Action::Hyperspace);
+    run(&mut ecs, &mut input, 1);
+
+    assert_eq!(ship_position(&ecs).unwrap(), after_jump);
+}
+
+#[test]
+fn missile_fired_on_a_jump_leaves_from_where_the_ship_came_out() {
+    let mut ecs = ship_world(1, true);
+    asteroid(&mut ecs, 1000.0, 100.0, 32, Vector2D::new(0.0, 0.0));
+    let mut input = InputState::default();
+
+    input.press(Action::Hyperspace);
+    input.press(Action::Fire);
+    run(&mut ecs, &mut input, 1);
+
+    let ship = ship_position(&ecs).unwrap();
+    assert!((ship - Vector2D::new(400.0, 300.0)).length() > 50.0);
+    let positions = ecs.read_storage::<components::Position>();
+    let missiles = ecs.read_storage::<components::Missile>();
+    let shots: Vec<Vector2D<f64>> = (&positions, &missiles).join().map(|(pos, _)| Vector2D::new(pos.x, pos.y)).collect();
+    assert!(!shots.is_empty());
+    for shot in shots {
+        assert!((shot - ship).length() < 50.0);
+    }
+}
+
+#[test]
+fn failed_hyperspace_jump_costs_a_life() {
+    // Some seed among these rolls a failure on the first jump.
+    let failed = (0..200).find_map(|seed| {
+        let mut ecs = ship_world(seed, false);
+        asteroid(&mut ecs, 1000.0, 100.0, 32, Vector2D::new(0.0, 0.0));
+        let mut input = InputState::default();
+        input.press(Action::Hyperspace);
+        input.press(Action::Fire);
+        run(&mut ecs, &mut input, 1);
+        ship_position(&ecs).is_none().then_some(ecs)
+    });
+
+    let ecs = failed.expect("no jump failed");
+    // The ship was lost before it could fire.
+    assert_eq!(ecs.read_storage::<components::Missile>().join().count(), 0);
+    let gamedatas = ecs.read_storage::<components::GameData>();
+    let gamedata = gamedatas.join().next().unwrap();
+    assert_eq!(gamedata.lives, 2);
+    assert_eq!(gamedata.respawn_timer, game::RESPAWN_DELAY);
+}
+
+#[test]
+fn bomb_shockwave_destroys_asteroids_as_it_reaches_them() {
+    let mut ecs = ship_world(1, false);
+    let near = asteroid(&mut ecs, 700.0, 300.0, 64, Vector2D::new(0.0, 0.0));
+    let far = asteroid(&mut ecs, 1200.0, 600.0, 64, Vector2D::new(0.0, 0.0));
+    let mut input = InputState::default();
+
+    input.press(Action::Bomb);
+    run(&mut ecs, &mut input, 1);
+    assert_eq!(ecs.read_storage::<components::Player>().join().next().unwrap().bombs, bomb::STARTING_BOMBS - 1);
+    assert!(bomb::slow_motion(&ecs));
+
+    input.release(Action::Bomb);
+    run(&mut ecs, &mut input, 16);
+
+    assert!(!ecs.is_alive(near));
+    assert!(ecs.is_alive(far));
+    // Destroyed whole, without breaking into pieces.
+    assert_eq!(ecs.read_storage::<components::Asteroid>().join().count(), 1);
+    let gamedatas = ecs.read_storage::<components::GameData>();
+    assert_eq!(gamedatas.join().next().unwrap().score, score::MEDIUM_ASTEROID_POINTS);
+}
+
+#[test]
+fn asteroid_reached_by_a_shockwave_and_a_missile_scores_once() {
+    let mut ecs = ship_world(1, false);
+    let target = asteroid(&mut ecs, 800.0, 300.0, 25, Vector2D::new(0.0, 0.0));
+    let shockwave = bomb::spawn_shockwave(&mut ecs, 700.0, 300.0);
+    ecs.write_storage::<components::Shockwave>().get_mut(shockwave).unwrap().radius = 80.0;
+    let missile = game::spawn_missile(&mut ecs, components::Position{x: 780.0, y: 300.0, rot: 90.0}, &Weapon{speed: 20.0, ..Weapon::default()});
+    let mut input = InputState::default();
+
+    run(&mut ecs, &mut input, 1);
+
+    assert!(!ecs.is_alive(target));
+    // The ring got there first, the missile flies on.
+    assert!(ecs.is_alive(missile));
+    let gamedatas = ecs.read_storage::<components::GameData>();
+    assert_eq!(gamedatas.join().next().unwrap().score, score::SMALL_ASTEROID_POINTS);
+}
+
+#[test]
+fn shockwave_is_gone_once_it_has_crossed_the_arena() {
+    let mut ecs = ship_world(1, false);
+    bomb::spawn_shockwave(&mut ecs, 400.0, 300.0);
+    let mut input = InputState::default();
+
+    run(&mut ecs, &mut input, 20);
+    assert!(!bomb::slow_motion(&ecs));
+
+    let ticks = (bomb::SHOCKWAVE_MAX_RADIUS / bomb::SHOCKWAVE_SPEED) as u32 + 1;
+    run(&mut ecs, &mut input, ticks - 20);
+
+    assert_eq!(ecs.read_storage::<components::Shockwave>().join().count(), 0);
+}
+
+#[test]
+fn bomb_does_nothing_without_bombs_left() {
+    let mut ecs = ship_world(1, false);
+    for player in (&mut ecs.write_storage::<components::Player>()).join() {
+        player.bombs = 0;
+    }
+    let mut input = InputState::default();
+
+    input.press(Action::Bomb);
+    run(&mut ecs, &mut input, 1);
+
+    assert_eq!(ecs.read_storage::<components::Shockwave>().join().count(), 0);
+}
@@ -0,0 +1,220 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use specs::{World, WorldExt, Join, Entity};
+use vector2d::Vector2D;
+
+use asteroid_game::archetypes::{Archetype, Archetypes, SpawnWeight, SplitRule};
+use asteroid_game::components;
+use asteroid_game::game;
+use asteroid_game::resources::{Arena, Rules};
+use asteroid_game::weapons::Weapon;
+
+use common::step;
+
+fn asteroid_world(archetype: Archetype) -> World {
+    let mut ecs = game::create_world(Arena::default(), 1);
+    ecs.insert(Archetypes{list: vec![archetype]});
+    ecs
+}
+
+// A big asteroid moving up, with a missile about to hit it from the left.
+fn shoot_asteroid(ecs: &mut World, damage: u32) -> Entity {
+    let asteroid = game::create_asteroid(ecs, components::Position{x: 400.0, y: 300.0, rot: 0.0}, 128);
+    game::spawn_missile(ecs, components::Position{x: 330.0, y: 300.0, rot: 90.0}, &Weapon{damage, ..Weapon::default()});
+    asteroid
+}
+
+// A world where asteroids bounce off each other or not, with two of them flying into each other.
+fn colliding_asteroids(asteroid_collisions: bool) -> (World, Entity, Entity) {
+    let mut ecs = common::empty_world(1, false);
+    ecs.insert(Rules{asteroid_collisions, ..Rules::default()});
+    let big = moving_asteroid(&mut ecs, 400.0, 300.0, 64, Vector2D::new(2.0, 0.0));
+    let small = moving_asteroid(&mut ecs, 438.0, 300.0, 32, Vector2D::new(-2.0, 1.0));
+    (ecs, big, small)
+}
+
+fn moving_asteroid(ecs: &mut World, x: f64, y: f64, size: u32, velocity: Vector2D<f64>) -> Entity {
+    let asteroid = common::still_asteroid(ecs, x, y, size);
+    ecs.write_storage::<components::Velocity>().get_mut(asteroid).unwrap().0 = velocity;
+    asteroid
+}
+
+fn momentum(ecs: &World) -> Vector2D<f64> {
+    let velocities = ecs.read_storage::<components::Velocity>();
+    let rends = ecs.read_storage::<components::Renderable>();
+    let asteroids = ecs.read_storage::<components::Asteroid>();
+    (&velocities, &rends, &asteroids).join()
+        .fold(Vector2D::new(0.0, 0.0), |sum, (velocity, rend, _)| sum + velocity.0 * (rend.o_w * rend.o_w) as f64)
+}
+
+fn center(ecs: &World, asteroid: Entity) -> Vector2D<f64> {
+    let positions = ecs.read_storage::<components::Position>();
+    let pos = positions.get(asteroid).unwrap();
+    Vector2D::new(pos.x, pos.y)
+}
+
+fn velocity(ecs: &World, asteroid: Entity) -> Vector2D<f64> {
+    ecs.read_storage::<components::Velocity>().get(asteroid).unwrap().0
+}
+
+#[test]
+fn health_is_scaled_by_size() {
+    let archetype = Archetype{size_per_health: 32, ..Archetype::default()};
+    assert_eq!(archetype.health(128), 4);
+    assert_eq!(archetype.health(50), 2);
+    assert_eq!(archetype.health(10), 1);
+}
+
+#[test]
+fn asteroid_survives_a_hit_below_its_health_and_flashes() {
+    let mut ecs = asteroid_world(Archetype::default());
+    let asteroid = shoot_asteroid(&mut ecs, 1);
+
+    step(&mut ecs);
+
+    assert!(ecs.is_alive(asteroid));
+    assert_eq!(ecs.read_storage::<components::Health>().get(asteroid).unwrap().current, 3);
+    assert!(ecs.read_storage::<components::HitFlash>().get(asteroid).unwrap().ticks > 0);
+    assert_eq!(ecs.read_storage::<components::Missile>().join().count(), 0);
+}
+
+#[test]
+fn asteroid_splits_following_its_split_rule() {
+    let split = SplitRule{count: 3, spread: 90.0, speed: 2.0, min_size: 25, kind: None};
+    let mut ecs = asteroid_world(Archetype{split, ..Archetype::default()});
+    let asteroid = shoot_asteroid(&mut ecs, 4);
+
+    step(&mut ecs);
+
+    assert!(!ecs.is_alive(asteroid));
+    let velocities = ecs.read_storage::<components::Velocity>();
+    let asteroids = ecs.read_storage::<components::Asteroid>();
+    let rends = ecs.read_storage::<components::Renderable>();
+    let pieces: Vec<(Vector2D<f64>, u32)> = (&velocities, &asteroids, &rends).join()
+        .map(|(velocity, _, rend)| (velocity.0, rend.o_w))
+        .collect();
+
+    assert_eq!(pieces.len(), 3);
+    for (velocity, size) in pieces {
+        assert_eq!(size, 64);
+        assert!((velocity.length() - Archetype::default().min_speed * 2.0).abs() < 1e-9);
+        // Spread over 90 degrees around straight up.
+        assert!(velocity.y < 0.0 && velocity.x.abs() <= -velocity.y + 1e-9);
+    }
+}
+
+#[test]
+fn explosive_asteroid_destroys_its_neighbours() {
+    let explosive = Archetype{
+        name: "explosive".to_string(),
+        explosive: Some(components::Explosive{radius: 100.0, damage: 10}),
+        split: SplitRule{count: 0, ..Archetype::default().split},
+        ..Archetype::default()
+    };
+    let mut ecs = asteroid_world(Archetype::default());
+    let near = game::spawn_asteroid(&mut ecs, components::Position{x: 450.0, y: 300.0, rot: 0.0}, 25, &Archetype::default(), 0.0);
+    let far = game::spawn_asteroid(&mut ecs, components::Position{x: 600.0, y: 300.0, rot: 0.0}, 25, &Archetype::default(), 0.0);
+    let asteroid = game::spawn_asteroid(&mut ecs, components::Position{x: 400.0, y: 300.0, rot: 0.0}, 25, &explosive, 0.0);
+    game::spawn_missile(&mut ecs, components::Position{x: 370.0, y: 300.0, rot: 90.0}, &Weapon{speed: 20.0, ..Weapon::default()});
+
+    step(&mut ecs);
+
+    assert!(!ecs.is_alive(asteroid));
+    assert!(!ecs.is_alive(near));
+    assert!(ecs.is_alive(far));
+}
+
+#[test]
+fn chilling_asteroid_slows_down_a_ship_nearby() {
+    let ice = Archetype{chilling: Some(components::Chilling{radius: 200.0, slowdown: 0.5}), ..Archetype::default()};
+    let mut ecs = asteroid_world(ice.clone());
+    game::spawn_player(&mut ecs, components::Position{x: 400.0, y: 300.0, rot: 0.0}, 0);
+    game::spawn_asteroid(&mut ecs, components::Position{x: 550.0, y: 300.0, rot: 0.0}, 25, &ice, 0.0);
+    {
+        let players = ecs.read_storage::<components::Player>();
+        let mut velocities = ecs.write_storage::<components::Velocity>();
+        for (velocity, _) in (&mut velocities, &players).join() {
+            velocity.0 = Vector2D::new(2.0, 0.0);
+        }
+    }
+
+    step(&mut ecs);
+
+    let players = ecs.read_storage::<components::Player>();
+    let velocities = ecs.read_storage::<components::Velocity>();
+    let (velocity, _) = (&velocities, &players).join().next().unwrap();
+    assert_eq!(velocity.0.x, 1.0);
+}
+
+#[test]
+fn spawn_weights_change_with_the_level() {
+    let late = Archetype{name: "late".to_string(), spawn: vec![SpawnWeight{level: 5, weight: 1000}], ..Archetype::default()};
+    let archetypes = Archetypes{list: vec![Archetype::default(), late]};
+    let mut rng = StdRng::seed_from_u64(1);
+
+    assert!((0..100).all(|_| archetypes.pick(4, &mut rng).name == "rock"));
+    assert!((0..100).filter(|_| archetypes.pick(5, &mut rng).name == "late").count() > 90);
+}
+
+#[test]
+fn asteroids_file_in_the_repository_is_valid() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("data/asteroids.toml");
+    let archetypes = Archetypes::parse(&fs::read_to_string(path).unwrap()).unwrap();
+    for archetype in &archetypes.list {
+        assert!(Path::new(env!("CARGO_MANIFEST_DIR")).join(&archetype.texture).exists(), "{} is missing", archetype.texture);
+    }
+}
+
+#[test]
+fn splitting_into_an_unknown_kind_is_refused() {
+    let broken = Archetype{split: SplitRule{kind: Some("marble".to_string()), ..Archetype::default().split}, ..Archetype::default()};
+    assert!(Archetypes{list: vec![broken]}.validate().is_err());
+}
+
+#[test]
+fn bouncing_asteroids_keep_their_momentum() {
+    let (mut ecs, big, small) = colliding_asteroids(true);
+    let before = momentum(&ecs);
+
+    step(&mut ecs);
+
+    let after = momentum(&ecs);
+    assert!((after - before).length() < 1e-9);
+    // The small one bounced back, the big one was slowed down.
+    assert!(velocity(&ecs, small).x > 0.0);
+    assert!(velocity(&ecs, big).x < 2.0);
+}
+
+#[test]
+fn overlapping_asteroids_are_pushed_apart() {
+    let mut ecs = common::empty_world(1, false);
+    ecs.insert(Rules{asteroid_collisions: true, ..Rules::default()});
+    let first = common::still_asteroid(&mut ecs, 400.0, 300.0, 64);
+    let second = common::still_asteroid(&mut ecs, 420.0, 300.0, 64);
+
+    step(&mut ecs);
+
+    assert!((center(&ecs, second) - center(&ecs, first)).length() >= 64.0 - 1e-9);
+    // Just as heavy, so both moved as far. Resting asteroids aren't set in motion.
+    assert!((center(&ecs, first).x - 378.0).abs() < 1e-9);
+    assert!((center(&ecs, second).x - 442.0).abs() < 1e-9);
+    assert_eq!(velocity(&ecs, first), Vector2D::new(0.0, 0.0));
+    assert_eq!(velocity(&ecs, second), Vector2D::new(0.0, 0.0));
+}
+
+#[test]
+fn asteroids_pass_through_each_other_without_the_rule() {
+    let (mut ecs, big, small) = colliding_asteroids(false);
+
+    step(&mut ecs);
+
+    assert_eq!(velocity(&ecs, big), Vector2D::new(2.0, 0.0));
+    assert_eq!(velocity(&ecs, small), Vector2D::new(-2.0, 1.0));
+    assert_eq!(center(&ecs, big), Vector2D::new(402.0, 300.0));
+    assert_eq!(center(&ecs, small), Vector2D::new(436.0, 301.0));
+}
@@ -0,0 +1,179 @@
+mod common;
+
+use std::path::Path;
+
+use specs::{World, WorldExt, Entity};
+use vector2d::Vector2D;
+
+use asteroid_game::collision::{segment_hits_circle, shapes_overlap, hull_from_alpha, load_hull, Shape};
+use asteroid_game::components;
+use asteroid_game::game;
+use asteroid_game::resources::Hulls;
+use asteroid_game::score::SMALL_ASTEROID_POINTS;
+use asteroid_game::weapons::Weapon;
+
+use common::{empty_world, still_asteroid, step, score};
+
+// Missile flying right at the given speed.
+fn missile(ecs: &mut World, x: f64, y: f64, speed: f64) -> Entity {
+    game::spawn_missile(ecs, components::Position{x, y, rot: 90.0}, &Weapon{speed, ..Weapon::default()})
+}
+
+#[test]
+fn segment_through_circle_hits_where_it_enters() {
+    let hit = segment_hits_circle(Vector2D::new(-20.0, 0.0), Vector2D::new(20.0, 0.0), 10.0);
+    assert_eq!(hit, Some(0.25));
+}
+
+#[test]
+fn segment_passing_beside_circle_misses() {
+    assert_eq!(segment_hits_circle(Vector2D::new(-20.0, 11.0), Vector2D::new(20.0, 11.0), 10.0), None);
+}
+
+#[test]
+fn segment_ending_before_circle_misses() {
+    assert_eq!(segment_hits_circle(Vector2D::new(-40.0, 0.0), Vector2D::new(-15.0, 0.0), 10.0), None);
+}
+
+#[test]
+fn segment_starting_inside_circle_hits_immediately() {
+    assert_eq!(segment_hits_circle(Vector2D::new(2.0, 0.0), Vector2D::new(50.0, 0.0), 10.0), Some(0.0));
+}
+
+#[test]
+fn fast_missile_hits_small_asteroid_it_crosses_in_one_tick() {
+    let mut ecs = empty_world(1, false);
+    // The missile starts left of the 25px fragment and ends the tick well past it,
+    // so neither end point is inside.
+    let asteroid = still_asteroid(&mut ecs, 400.0, 300.0, 25);
+    let missile = missile(&mut ecs, 370.0, 300.0, 60.0);
+
+    step(&mut ecs);
+
+    assert!(!ecs.is_alive(asteroid));
+    assert!(!ecs.is_alive(missile));
+    assert_eq!(score(&ecs), SMALL_ASTEROID_POINTS);
+}
+
+#[test]
+fn fast_missile_passing_beside_small_asteroid_misses() {
+    let mut ecs = empty_world(1, false);
+    let asteroid = still_asteroid(&mut ecs, 400.0, 300.0, 25);
+    let missile = missile(&mut ecs, 370.0, 314.0, 60.0);
+
+    step(&mut ecs);
+
+    assert!(ecs.is_alive(asteroid));
+    assert!(ecs.is_alive(missile));
+    assert_eq!(score(&ecs), 0);
+}
+
+#[test]
+fn missile_only_destroys_first_asteroid_on_its_path() {
+    let mut ecs = empty_world(1, false);
+    let near = still_asteroid(&mut ecs, 400.0, 300.0, 25);
+    let far = still_asteroid(&mut ecs, 440.0, 300.0, 25);
+    missile(&mut ecs, 370.0, 300.0, 100.0);
+
+    step(&mut ecs);
+
+    assert!(!ecs.is_alive(near));
+    assert!(ecs.is_alive(far));
+    assert_eq!(score(&ecs), SMALL_ASTEROID_POINTS);
+}
+
+#[test]
+fn missile_hits_asteroid_moving_across_its_path() {
+    let mut ecs = empty_world(1, false);
+    // The asteroid moves down through the missile's path while the missile moves right,
+    // they only meet halfway through the tick.
+    let asteroid = still_asteroid(&mut ecs, 400.0, 270.0, 25);
+    ecs.write_storage::<components::Velocity>().insert(asteroid, components::Velocity(Vector2D::new(0.0, 60.0))).unwrap();
+    missile(&mut ecs, 370.0, 300.0, 60.0);
+
+    step(&mut ecs);
+
+    assert!(!ecs.is_alive(asteroid));
+    assert_eq!(score(&ecs), SMALL_ASTEROID_POINTS);
+}
+
+fn square(half_size: f64) -> Shape {
+    Shape::Box{half_width: half_size, half_height: half_size}
+}
+
+// The ship's outline: tip at the top, full width at the bottom.
+fn triangle() -> Vec<Vector2D<f64>> {
+    vec![Vector2D::new(0.0, -0.5), Vector2D::new(0.5, 0.5), Vector2D::new(-0.5, 0.5)]
+}
+
+#[test]
+fn boxes_side_by_side_only_overlap_when_turned() {
+    let (left, right) = (Vector2D::new(0.0, 0.0), Vector2D::new(11.0, 0.0));
+    assert!(!shapes_overlap(&square(5.0), left, 0.0, &square(5.0), right, 0.0));
+    // Turned 45 degrees a corner reaches about 7 pixels out.
+    assert!(shapes_overlap(&square(5.0), left, 45.0, &square(5.0), right, 0.0));
+}
+
+#[test]
+fn circle_next_to_polygon_corner_misses() {
+    let points: Vec<Vector2D<f64>> = triangle().iter().map(|point| *point * 64.0).collect();
+    let ship = Shape::Polygon{points};
+    let asteroid = Shape::Circle{radius: 12.5};
+
+    // Inside the old 32 pixel circle around the ship, but beside the tip.
+    assert!(!shapes_overlap(&ship, Vector2D::new(0.0, 0.0), 0.0, &asteroid, Vector2D::new(25.0, -25.0), 0.0));
+    // Turned so the tip points at it.
+    assert!(shapes_overlap(&ship, Vector2D::new(0.0, 0.0), 45.0, &asteroid, Vector2D::new(25.0, -25.0), 0.0));
+}
+
+#[test]
+fn hull_from_alpha_outlines_solid_pixels() {
+    // A 4x4 sprite with a solid 2x2 square in the middle.
+    let mut alpha = vec![0u8; 16];
+    for (x, y) in [(1, 1), (2, 1), (1, 2), (2, 2)] {
+        alpha[y * 4 + x] = 255;
+    }
+
+    let hull = hull_from_alpha(4, 4, &alpha);
+    assert_eq!(hull.len(), 4);
+    for point in hull {
+        assert_eq!(point.x.abs(), 0.25);
+        assert_eq!(point.y.abs(), 0.25);
+    }
+}
+
+#[test]
+fn hull_from_alpha_of_empty_sprite_is_empty() {
+    assert!(hull_from_alpha(4, 4, &[0u8; 16]).is_empty());
+}
+
+#[test]
+fn ship_hull_is_read_from_png_without_sdl() {
+    let hull = load_hull(Path::new("img/ship.png")).unwrap();
+    assert!(hull.len() >= 3);
+    assert!(hull.iter().all(|point| point.x.abs() <= 0.5 && point.y.abs() <= 0.5));
+    // The ship faces up, so its tip is the topmost point.
+    let tip = hull.iter().min_by(|a, b| a.y.total_cmp(&b.y)).unwrap();
+    assert_eq!(tip.y, -0.5);
+    assert!(tip.x.abs() < 0.05);
+}
+
+#[test]
+fn missing_image_is_an_error() {
+    assert!(load_hull(Path::new("img/missing.png")).is_err());
+}
+
+#[test]
+fn ship_with_hull_survives_asteroid_beside_its_tip() {
+    let mut ecs = empty_world(1, false);
+    let mut hulls = Hulls::default();
+    hulls.shapes.insert("img/ship.png".to_string(), triangle());
+    ecs.insert(hulls);
+
+    game::spawn_player(&mut ecs, components::Position{x: 400.0, y: 300.0, rot: 0.0}, 0);
+    still_asteroid(&mut ecs, 425.0, 275.0, 25);
+
+    step(&mut ecs);
+
+    assert_eq!(ecs.read_storage::<components::Player>().count(), 1);
+}
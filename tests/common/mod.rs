@@ -0,0 +1,50 @@
+// Helpers shared by the integration tests, not every test file uses all of them.
+#![allow(dead_code)]
+
+use specs::{World, WorldExt, Builder, Join, Entity};
+use vector2d::Vector2D;
+
+use asteroid_game::components;
+use asteroid_game::game;
+use asteroid_game::resources::Arena;
+
+// A world with only a new game's data, so nothing but what a test creates can collide.
+pub fn empty_world(seed: u64, god_mode: bool) -> World {
+    let mut ecs = game::create_world(Arena::default(), seed);
+    ecs.create_entity()
+        .with(components::GameData::new(god_mode))
+        .build();
+    ecs
+}
+
+// An empty world with a ship at (x, y) facing up.
+pub fn ship_world(x: f64, y: f64, seed: u64, god_mode: bool) -> World {
+    let mut ecs = empty_world(seed, god_mode);
+    game::spawn_player(&mut ecs, components::Position{x, y, rot: 0.0}, 0);
+    ecs
+}
+
+pub fn ship(ecs: &World) -> Entity {
+    (&ecs.entities(), &ecs.read_storage::<components::Player>()).join().next().unwrap().0
+}
+
+pub fn still_asteroid(ecs: &mut World, x: f64, y: f64, size: u32) -> Entity {
+    let asteroid = game::create_asteroid(ecs, components::Position{x, y, rot: 0.0}, size);
+    ecs.write_storage::<components::Velocity>().insert(asteroid, components::Velocity(Vector2D::new(0.0, 0.0))).unwrap();
+    asteroid
+}
+
+// Runs every system once, without any player input.
+pub fn step(ecs: &mut World) {
+    let mut dispatcher = game::build_dispatcher();
+    dispatcher.dispatch(ecs);
+    ecs.maintain();
+}
+
+pub fn gamedata<T>(ecs: &World, field: impl Fn(&components::GameData) -> T) -> T {
+    field(ecs.read_storage::<components::GameData>().join().next().unwrap())
+}
+
+pub fn score(ecs: &World) -> u32 {
+    gamedata(ecs, |gamedata| gamedata.score)
+}
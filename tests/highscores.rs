@@ -0,0 +1,99 @@
+use std::fs;
+use std::path::PathBuf;
+
+use asteroid_game::highscores::{self, civil_from_days, HighScore, HighScoreTable, MAX_ENTRIES};
+
+// A file name of its own for every test, in the system's temporary directory.
+fn temp_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("asteroid-game-highscores-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir.join(name)
+}
+
+fn entry(name: &str, score: u32) -> HighScore {
+    HighScore{name: name.to_string(), score, level: 1, date: "2024-05-01".to_string(), seed: 1}
+}
+
+fn scores(table: &HighScoreTable) -> Vec<u32> {
+    table.scores().iter().map(|entry| entry.score).collect()
+}
+
+#[test]
+fn missing_file_gives_an_empty_table() {
+    let table = HighScoreTable::load(&temp_path("missing.toml"));
+
+    assert!(table.scores().is_empty());
+}
+
+#[test]
+fn corrupt_file_is_moved_aside() {
+    let path = temp_path("corrupt.toml");
+    fs::write(&path, "this isn't [[toml").unwrap();
+
+    let table = HighScoreTable::load(&path);
+
+    assert!(table.scores().is_empty());
+    assert!(!path.exists());
+    assert_eq!(fs::read_to_string(path.with_extension("toml.bak")).unwrap(), "this isn't [[toml");
+}
+
+#[test]
+fn saved_table_loads_back_highest_first() {
+    let path = temp_path("saved.toml");
+    let mut table = HighScoreTable::default();
+    table.insert(entry("AAA", 100));
+    table.insert(entry("BBB", 300));
+    table.save(&path).unwrap();
+
+    let loaded = HighScoreTable::load(&path);
+
+    assert_eq!(scores(&loaded), [300, 100]);
+    assert_eq!(loaded.scores()[0].name, "BBB");
+}
+
+#[test]
+fn only_scores_beating_a_full_table_qualify() {
+    let mut table = HighScoreTable::default();
+    assert!(!table.qualifies(0));
+    assert!(table.qualifies(1));
+
+    for score in 1..=MAX_ENTRIES as u32 {
+        table.insert(entry("AAA", score * 10));
+    }
+
+    assert!(!table.qualifies(10));
+    assert!(table.qualifies(11));
+}
+
+#[test]
+fn insert_keeps_the_order_and_the_best_ten() {
+    let mut table = HighScoreTable::default();
+    for score in [50, 10, 30, 20, 40, 60, 80, 70, 90, 100] {
+        table.insert(entry("AAA", score));
+    }
+
+    // Ties go below the score that was there first.
+    assert_eq!(table.insert(entry("BBB", 50)), Some(6));
+    assert_eq!(table.insert(entry("CCC", 5)), None);
+
+    assert_eq!(table.scores().len(), MAX_ENTRIES);
+    assert_eq!(scores(&table), [100, 90, 80, 70, 60, 50, 50, 40, 30, 20]);
+    assert_eq!(table.scores()[5].name, "AAA");
+    assert_eq!(table.scores()[6].name, "BBB");
+}
+
+#[test]
+fn empty_name_is_saved_as_the_placeholder() {
+    assert_eq!(highscores::name_or_default(""), highscores::DEFAULT_NAME);
+    assert_eq!(highscores::name_or_default("AB"), "AB");
+}
+
+#[test]
+fn days_convert_to_dates() {
+    assert_eq!(civil_from_days(0), (1970, 1, 1));
+    assert_eq!(civil_from_days(59), (1970, 3, 1));
+    // Leap day of a year divisible by 400.
+    assert_eq!(civil_from_days(11016), (2000, 2, 29));
+    assert_eq!(civil_from_days(19844), (2024, 5, 1));
+    assert_eq!(civil_from_days(-1), (1969, 12, 31));
+}
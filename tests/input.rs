@@ -0,0 +1,105 @@
+use asteroid_game::input::{Action, InputEvent, InputMap, InputState};
+
+// Stands in for SDL's key names.
+fn known_key(name: &str) -> bool {
+    ["W", "Z", "Q", "D", "Space"].contains(&name)
+}
+
+#[test]
+fn keys_can_be_rebound_and_swapped() {
+    let input_map = InputMap::parse("[keyboard]\nThrust = \"Z\"\nRotateLeft = \"Q\"\nRotateRight = \"D\"\n", known_key).unwrap();
+
+    assert_eq!(input_map.action_for("Z"), Some(Action::Thrust));
+    assert_eq!(input_map.action_for("Q"), Some(Action::RotateLeft));
+    assert_eq!(input_map.action_for("W"), None);
+}
+
+#[test]
+fn unknown_key_name_is_rejected() {
+    let error = InputMap::parse("[keyboard]\nThrust = \"Banana\"\n", known_key).err().unwrap();
+
+    assert!(error.contains("Banana"), "{}", error);
+}
+
+#[test]
+fn unknown_action_is_rejected() {
+    let error = InputMap::parse("[keyboard]\nWarp = \"Z\"\n", known_key).err().unwrap();
+
+    assert!(error.contains("Warp"), "{}", error);
+}
+
+#[test]
+fn key_bound_to_two_actions_names_both() {
+    let error = InputMap::parse("[keyboard]\nThrust = \"Space\"\n", known_key).err().unwrap();
+
+    assert!(error.contains("Thrust") && error.contains("Fire"), "{}", error);
+}
+
+#[test]
+fn controller_input_bound_to_two_actions_names_both() {
+    let error = InputMap::parse("[gamepad]\nThrust = \"a\"\n", known_key).err().unwrap();
+
+    assert!(error.contains("Thrust") && error.contains("Fire"), "{}", error);
+}
+
+#[test]
+fn press_is_only_new_for_one_tick_while_held_lasts() {
+    let mut input = InputState::default();
+
+    input.press(Action::Fire);
+    assert!(input.just_pressed(Action::Fire));
+    assert!(input.is_held(Action::Fire));
+
+    input.end_tick();
+    assert!(!input.just_pressed(Action::Fire));
+    assert!(input.is_held(Action::Fire));
+
+    // Pressing what is already held isn't a new press.
+    input.press(Action::Fire);
+    assert!(!input.just_pressed(Action::Fire));
+    assert!(input.events().is_empty());
+}
+
+#[test]
+fn release_is_only_new_for_one_tick() {
+    let mut input = InputState::default();
+    input.press(Action::Thrust);
+    input.end_tick();
+
+    input.release(Action::Thrust);
+    assert!(input.just_released(Action::Thrust));
+    assert!(!input.is_held(Action::Thrust));
+
+    input.end_tick();
+    assert!(!input.just_released(Action::Thrust));
+    // Nothing to release a second time.
+    input.release(Action::Thrust);
+    assert!(!input.just_released(Action::Thrust));
+}
+
+#[test]
+fn tap_between_two_ticks_still_counts() {
+    let mut input = InputState::default();
+
+    input.press(Action::Hyperspace);
+    input.release(Action::Hyperspace);
+
+    assert!(input.just_pressed(Action::Hyperspace));
+    assert!(input.just_released(Action::Hyperspace));
+    assert!(!input.is_held(Action::Hyperspace));
+    assert_eq!(input.events(), &[InputEvent::Press(Action::Hyperspace), InputEvent::Release(Action::Hyperspace)][..]);
+}
+
+#[test]
+fn release_all_lets_go_of_keys_and_centers_axes() {
+    let mut input = InputState::default();
+    input.press(Action::Fire);
+    input.set_axis(Action::Thrust, 0.5);
+    input.end_tick();
+
+    input.release_all();
+
+    assert!(!input.is_held(Action::Fire));
+    assert!(input.just_released(Action::Fire));
+    assert_eq!(input.strength(Action::Thrust), 0.0);
+}
@@ -0,0 +1,148 @@
+mod common;
+
+use specs::{World, WorldExt, Join};
+
+use asteroid_game::components;
+use asteroid_game::game;
+use asteroid_game::input::InputState;
+use asteroid_game::resources::Arena;
+
+use common::gamedata;
+
+fn run(ecs: &mut World, ticks: u32) {
+    let mut dispatcher = game::build_dispatcher();
+    let mut input = InputState::default();
+    for _ in 0..ticks {
+        game::step(ecs, &mut dispatcher, &mut input);
+    }
+}
+
+fn ship_position(ecs: &World) -> Option<(f64, f64)> {
+    let positions = ecs.read_storage::<components::Position>();
+    let players = ecs.read_storage::<components::Player>();
+    (&positions, &players).join().next().map(|(pos, _)| (pos.x, pos.y))
+}
+
+fn arena_center(ecs: &World) -> (f64, f64) {
+    let arena = ecs.read_resource::<Arena>();
+    (arena.width as f64 / 2.0, arena.height as f64 / 2.0)
+}
+
+// A ship at (400, 300) with an asteroid right on top of it, and one far off in a corner so
+// clearing the level doesn't spawn new ones.
+fn doomed_ship_world() -> World {
+    let mut ecs = common::ship_world(400.0, 300.0, 1, false);
+    common::still_asteroid(&mut ecs, 400.0, 300.0, 64);
+    common::still_asteroid(&mut ecs, 60.0, 60.0, 32);
+    ecs
+}
+
+#[test]
+fn new_game_starts_with_every_ship() {
+    let ecs = common::empty_world(1, false);
+    assert_eq!(gamedata(&ecs, |gamedata| gamedata.lives), game::STARTING_LIVES);
+}
+
+#[test]
+fn asteroid_hitting_the_ship_costs_a_life() {
+    let mut ecs = doomed_ship_world();
+
+    run(&mut ecs, 1);
+
+    assert!(ship_position(&ecs).is_none());
+    assert_eq!(gamedata(&ecs, |gamedata| gamedata.lives), game::STARTING_LIVES - 1);
+    assert_eq!(gamedata(&ecs, |gamedata| gamedata.respawn_timer), game::RESPAWN_DELAY);
+    assert!(!game::is_game_over(&ecs));
+}
+
+#[test]
+fn lost_ship_comes_back_at_the_center_after_the_delay() {
+    let mut ecs = doomed_ship_world();
+    run(&mut ecs, 1);
+
+    run(&mut ecs, game::RESPAWN_DELAY - 1);
+    assert!(ship_position(&ecs).is_none());
+    run(&mut ecs, 1);
+    assert_eq!(ship_position(&ecs), Some(arena_center(&ecs)));
+}
+
+#[test]
+fn lost_ship_waits_for_the_center_to_clear() {
+    let mut ecs = doomed_ship_world();
+    run(&mut ecs, 1);
+    let (x, y) = arena_center(&ecs);
+    let blocker = common::still_asteroid(&mut ecs, x + 100.0, y, 32);
+
+    run(&mut ecs, game::RESPAWN_DELAY * 2);
+    assert!(ship_position(&ecs).is_none());
+
+    ecs.delete_entity(blocker).unwrap();
+    run(&mut ecs, 1);
+    assert_eq!(ship_position(&ecs), Some((x, y)));
+}
+
+#[test]
+fn new_ship_is_invulnerable_until_its_countdown_runs_out() {
+    let mut ecs = doomed_ship_world();
+    run(&mut ecs, game::RESPAWN_DELAY + 1);
+    let (x, y) = arena_center(&ecs);
+    common::still_asteroid(&mut ecs, x, y, 64);
+
+    let invulnerable = |ecs: &World| ecs.read_storage::<components::Player>().join().next().unwrap().invulnerable;
+    // The tick it respawned on already counted.
+    assert_eq!(invulnerable(&ecs), game::INVULNERABLE_TICKS - 1);
+
+    run(&mut ecs, game::INVULNERABLE_TICKS - 2);
+    assert!(ship_position(&ecs).is_some());
+    assert_eq!(invulnerable(&ecs), 1);
+
+    run(&mut ecs, 1);
+    assert!(ship_position(&ecs).is_none());
+    assert_eq!(gamedata(&ecs, |gamedata| gamedata.lives), game::STARTING_LIVES - 2);
+}
+
+#[test]
+fn extra_life_is_awarded_at_every_score_threshold() {
+    let mut ecs = common::ship_world(400.0, 300.0, 1, false);
+    common::still_asteroid(&mut ecs, 60.0, 60.0, 32);
+    let set_score = |ecs: &mut World, score: u32| {
+        for gamedata in (&mut ecs.write_storage::<components::GameData>()).join() {
+            gamedata.score = score;
+        }
+    };
+
+    set_score(&mut ecs, game::EXTRA_LIFE_SCORE - 1);
+    run(&mut ecs, 1);
+    assert_eq!(gamedata(&ecs, |gamedata| gamedata.lives), game::STARTING_LIVES);
+
+    set_score(&mut ecs, game::EXTRA_LIFE_SCORE);
+    run(&mut ecs, 1);
+    assert_eq!(gamedata(&ecs, |gamedata| gamedata.lives), game::STARTING_LIVES + 1);
+    // Only once per threshold.
+    run(&mut ecs, 1);
+    assert_eq!(gamedata(&ecs, |gamedata| gamedata.lives), game::STARTING_LIVES + 1);
+
+    set_score(&mut ecs, game::EXTRA_LIFE_SCORE * 2);
+    run(&mut ecs, 1);
+    assert_eq!(gamedata(&ecs, |gamedata| gamedata.lives), game::STARTING_LIVES + 2);
+    assert_eq!(gamedata(&ecs, |gamedata| gamedata.next_extra_life), game::EXTRA_LIFE_SCORE * 3);
+}
+
+#[test]
+fn losing_the_last_ship_ends_the_game_and_keeps_the_score() {
+    let mut ecs = doomed_ship_world();
+    for gamedata in (&mut ecs.write_storage::<components::GameData>()).join() {
+        gamedata.lives = 1;
+        gamedata.score = 1230;
+    }
+
+    run(&mut ecs, 1);
+    assert!(game::is_game_over(&ecs));
+    assert_eq!(gamedata(&ecs, |gamedata| gamedata.lives), 0);
+
+    // No ship comes back and the score stays as it was.
+    run(&mut ecs, game::RESPAWN_DELAY * 2);
+    assert!(ship_position(&ecs).is_none());
+    assert!(game::is_game_over(&ecs));
+    assert_eq!(common::score(&ecs), 1230);
+}
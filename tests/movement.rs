@@ -0,0 +1,132 @@
+mod common;
+
+use specs::{World, WorldExt, Builder, Entity};
+use vector2d::Vector2D;
+
+use asteroid_game::components::{self, BoundaryBehavior};
+
+use common::step;
+
+// A 32 pixel sprite at (x, y) with nothing but movement, in a 1280 by 640 arena.
+fn body(ecs: &mut World, x: f64, y: f64, velocity: Vector2D<f64>, behavior: BoundaryBehavior) -> Entity {
+    ecs.create_entity()
+        .with(components::Position{x, y, rot: 0.0})
+        .with(components::PreviousPosition{x, y})
+        .with(components::Velocity(velocity))
+        .with(components::Renderable{
+            tex_name: String::new(),
+            i_w: 32,
+            i_h: 32,
+            o_w: 32,
+            o_h: 32,
+            frame: 0,
+            total_frames: 1,
+            rot: 0.0
+        })
+        .with(behavior)
+        .build()
+}
+
+fn position(ecs: &World, entity: Entity) -> (f64, f64) {
+    let positions = ecs.read_storage::<components::Position>();
+    let pos = positions.get(entity).unwrap();
+    (pos.x, pos.y)
+}
+
+fn velocity(ecs: &World, entity: Entity) -> Vector2D<f64> {
+    ecs.read_storage::<components::Velocity>().get(entity).unwrap().0
+}
+
+#[test]
+fn wrapping_entity_comes_back_on_the_other_side() {
+    let mut ecs = common::empty_world(1, false);
+    let right = body(&mut ecs, 1275.0, 320.0, Vector2D::new(10.0, 0.0), BoundaryBehavior::Wrap);
+    let top = body(&mut ecs, 100.0, 3.0, Vector2D::new(0.0, -5.0), BoundaryBehavior::Wrap);
+
+    step(&mut ecs);
+
+    assert_eq!(position(&ecs, right), (5.0, 320.0));
+    assert_eq!(position(&ecs, top), (100.0, 638.0));
+    assert_eq!(velocity(&ecs, right), Vector2D::new(10.0, 0.0));
+    // The previous position came along, the step is still 10 pixels long.
+    let previous_positions = ecs.read_storage::<components::PreviousPosition>();
+    assert_eq!(previous_positions.get(right).unwrap().x, -5.0);
+}
+
+#[test]
+fn bouncing_entity_turns_around_at_the_edge() {
+    let mut ecs = common::empty_world(1, false);
+    let leaving = body(&mut ecs, 1260.0, 320.0, Vector2D::new(5.0, 2.0), BoundaryBehavior::Bounce);
+    // Already heading back in, it mustn't be turned around again.
+    let returning = body(&mut ecs, 1270.0, 320.0, Vector2D::new(-1.0, 0.0), BoundaryBehavior::Bounce);
+
+    step(&mut ecs);
+
+    assert_eq!(position(&ecs, leaving), (1265.0, 322.0));
+    assert_eq!(velocity(&ecs, leaving), Vector2D::new(-5.0, 2.0));
+    assert_eq!(velocity(&ecs, returning), Vector2D::new(-1.0, 0.0));
+}
+
+#[test]
+fn despawning_entity_is_deleted_once_its_center_leaves() {
+    let mut ecs = common::empty_world(1, false);
+    let leaving = body(&mut ecs, 1275.0, 320.0, Vector2D::new(10.0, 0.0), BoundaryBehavior::Despawn);
+    // Its sprite sticks out, but the center is still inside.
+    let inside = body(&mut ecs, 1270.0, 320.0, Vector2D::new(5.0, 0.0), BoundaryBehavior::Despawn);
+
+    step(&mut ecs);
+
+    assert!(!ecs.is_alive(leaving));
+    assert!(ecs.is_alive(inside));
+}
+
+#[test]
+fn clamped_entity_stops_at_the_edge() {
+    let mut ecs = common::empty_world(1, false);
+    let entity = body(&mut ecs, 1260.0, 320.0, Vector2D::new(10.0, 3.0), BoundaryBehavior::Clamp);
+
+    step(&mut ecs);
+
+    // Only stopped along the edge it ran into.
+    assert_eq!(position(&ecs, entity), (1264.0, 323.0));
+    assert_eq!(velocity(&ecs, entity), Vector2D::new(0.0, 3.0));
+}
+
+#[test]
+fn lifetime_runs_out_after_its_ticks() {
+    let mut ecs = common::empty_world(1, false);
+    let entity = body(&mut ecs, 400.0, 300.0, Vector2D::new(1.0, 0.0), BoundaryBehavior::Wrap);
+    ecs.write_storage::<components::Lifetime>().insert(entity, components::Lifetime{ticks: Some(3), distance: None}).unwrap();
+
+    step(&mut ecs);
+    step(&mut ecs);
+    assert!(ecs.is_alive(entity));
+    step(&mut ecs);
+    assert!(!ecs.is_alive(entity));
+}
+
+#[test]
+fn lifetime_runs_out_after_its_distance() {
+    let mut ecs = common::empty_world(1, false);
+    // 5 pixels per tick.
+    let entity = body(&mut ecs, 400.0, 300.0, Vector2D::new(4.0, 3.0), BoundaryBehavior::Wrap);
+    ecs.write_storage::<components::Lifetime>().insert(entity, components::Lifetime{ticks: None, distance: Some(12.0)}).unwrap();
+
+    step(&mut ecs);
+    step(&mut ecs);
+    assert!(ecs.is_alive(entity));
+    step(&mut ecs);
+    assert!(!ecs.is_alive(entity));
+}
+
+#[test]
+fn lifetime_without_limits_never_runs_out() {
+    let mut ecs = common::empty_world(1, false);
+    let entity = body(&mut ecs, 400.0, 300.0, Vector2D::new(4.0, 3.0), BoundaryBehavior::Wrap);
+    ecs.write_storage::<components::Lifetime>().insert(entity, components::Lifetime{ticks: None, distance: None}).unwrap();
+
+    for _ in 0..1000 {
+        step(&mut ecs);
+    }
+    assert!(ecs.is_alive(entity));
+}
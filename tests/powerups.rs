@@ -0,0 +1,145 @@
+mod common;
+
+use specs::{World, WorldExt, Join, Entity};
+
+use asteroid_game::bomb;
+use asteroid_game::components::{self, PowerUpKind};
+use asteroid_game::game;
+use asteroid_game::input::{Action, InputState};
+use asteroid_game::powerup::{self, Drops};
+use asteroid_game::score;
+use asteroid_game::weapons::Weapon;
+
+use common::{still_asteroid, step, gamedata};
+
+// A ship at (400, 300) facing up, and nothing else.
+fn ship_world() -> (World, Entity) {
+    let ecs = common::ship_world(400.0, 300.0, 1, false);
+    let ship = common::ship(&ecs);
+    (ecs, ship)
+}
+
+// Drops a power-up right on top of the ship and lets it pick it up.
+fn collect(ecs: &mut World, kind: PowerUpKind) {
+    powerup::spawn_powerup(ecs, components::Position{x: 400.0, y: 300.0, rot: 0.0}, kind);
+    step(ecs);
+}
+
+#[test]
+fn asteroid_destroyed_by_the_player_may_drop_a_power_up() {
+    let (mut ecs, _) = ship_world();
+    still_asteroid(&mut ecs, 800.0, 300.0, 25);
+    game::spawn_missile(&mut ecs, components::Position{x: 780.0, y: 300.0, rot: 90.0}, &Weapon{speed: 20.0, ..Weapon::default()});
+
+    step(&mut ecs);
+
+    assert_eq!(ecs.read_resource::<Drops>().positions, [(800.0, 300.0)]);
+}
+
+#[test]
+fn only_some_destroyed_asteroids_drop_a_power_up() {
+    let (mut ecs, _) = ship_world();
+    ecs.write_resource::<Drops>().positions = vec![(800.0, 300.0); 1000];
+
+    powerup::drop_powerups(&mut ecs);
+
+    let dropped = ecs.read_storage::<components::PowerUp>().join().count();
+    assert!((40..=120).contains(&dropped), "{} dropped", dropped);
+    assert!(ecs.read_resource::<Drops>().positions.is_empty());
+}
+
+#[test]
+fn new_game_forgets_drops_from_the_last_one() {
+    let (mut ecs, _) = ship_world();
+    still_asteroid(&mut ecs, 800.0, 300.0, 25);
+    game::spawn_missile(&mut ecs, components::Position{x: 780.0, y: 300.0, rot: 90.0}, &Weapon{speed: 20.0, ..Weapon::default()});
+    step(&mut ecs);
+
+    game::new_game(&mut ecs);
+
+    assert!(ecs.read_resource::<Drops>().positions.is_empty());
+}
+
+#[test]
+fn power_up_disappears_after_a_while() {
+    let (mut ecs, _) = ship_world();
+    let pickup = powerup::spawn_powerup(&mut ecs, components::Position{x: 1000.0, y: 100.0, rot: 0.0}, PowerUpKind::Bomb);
+
+    for _ in 0..powerup::POWERUP_LIFETIME - 1 {
+        step(&mut ecs);
+    }
+    assert!(ecs.is_alive(pickup));
+    step(&mut ecs);
+    assert!(!ecs.is_alive(pickup));
+}
+
+#[test]
+fn rapid_fire_is_timed_and_shortens_the_cooldown() {
+    let (mut ecs, ship) = ship_world();
+    collect(&mut ecs, PowerUpKind::RapidFire);
+
+    assert_eq!(ecs.read_storage::<components::PowerUp>().join().count(), 0);
+    assert_eq!(ecs.read_storage::<components::ActiveEffects>().get(ship).unwrap().rapid_fire, powerup::EFFECT_TICKS);
+
+    let mut input = InputState::default();
+    input.press(Action::Fire);
+    game::update(&mut ecs, &input);
+    let cooldown = ecs.read_storage::<components::Player>().get(ship).unwrap().fire_cooldown;
+    assert_eq!(cooldown, Weapon::default().cooldown / powerup::RAPID_FIRE_FACTOR);
+
+    for _ in 0..powerup::EFFECT_TICKS {
+        step(&mut ecs);
+    }
+    assert_eq!(ecs.read_storage::<components::ActiveEffects>().get(ship).unwrap().rapid_fire, 0);
+}
+
+#[test]
+fn triple_shot_adds_a_shot_on_either_side() {
+    let (mut ecs, _) = ship_world();
+    collect(&mut ecs, PowerUpKind::TripleShot);
+    let mut input = InputState::default();
+
+    input.press(Action::Fire);
+    game::update(&mut ecs, &input);
+
+    let positions = ecs.read_storage::<components::Position>();
+    let missiles = ecs.read_storage::<components::Missile>();
+    let mut headings: Vec<f64> = (&positions, &missiles).join().map(|(pos, _)| pos.rot).collect();
+    headings.sort_by(f64::total_cmp);
+    assert_eq!(headings, [-15.0, 0.0, 15.0]);
+}
+
+#[test]
+fn shield_recharge_and_extra_life_apply_right_away() {
+    let (mut ecs, ship) = ship_world();
+    ecs.write_storage::<components::Player>().get_mut(ship).unwrap().shield = 0;
+
+    collect(&mut ecs, PowerUpKind::ShieldRecharge);
+    collect(&mut ecs, PowerUpKind::ExtraLife);
+
+    assert_eq!(ecs.read_storage::<components::Player>().get(ship).unwrap().shield, game::SHIELD_ENERGY);
+    assert_eq!(gamedata(&ecs, |gamedata| gamedata.lives), 4);
+}
+
+#[test]
+fn bomb_power_up_adds_a_bomb_up_to_the_maximum() {
+    let (mut ecs, ship) = ship_world();
+
+    for _ in 0..bomb::MAX_BOMBS {
+        collect(&mut ecs, PowerUpKind::Bomb);
+    }
+
+    assert_eq!(ecs.read_storage::<components::Player>().get(ship).unwrap().bombs, bomb::MAX_BOMBS);
+}
+
+#[test]
+fn score_multiplier_doubles_the_score() {
+    let (mut ecs, _) = ship_world();
+    collect(&mut ecs, PowerUpKind::ScoreMultiplier);
+    still_asteroid(&mut ecs, 800.0, 300.0, 25);
+    game::spawn_missile(&mut ecs, components::Position{x: 780.0, y: 300.0, rot: 90.0}, &Weapon{speed: 20.0, ..Weapon::default()});
+
+    step(&mut ecs);
+
+    assert_eq!(gamedata(&ecs, |gamedata| gamedata.score), score::SMALL_ASTEROID_POINTS * powerup::SCORE_MULTIPLIER);
+}
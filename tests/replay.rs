@@ -0,0 +1,70 @@
+use std::fs;
+
+use asteroid_game::components::BoundaryBehavior;
+use asteroid_game::input::InputState;
+use asteroid_game::replay::{Playback, Recorder, Replay};
+use asteroid_game::resources::Rules;
+
+fn play(text: &str, ticks: u32) -> Playback {
+    let mut playback = Playback::new(Replay::parse(text).unwrap());
+    let mut input = InputState::default();
+    for _ in 0..ticks {
+        playback.apply(&mut input);
+    }
+    playback
+}
+
+#[test]
+fn playback_finishes_after_the_recorded_length() {
+    let replay = "seed 1\nticks 3\n0 down Thrust\n";
+
+    assert!(!play(replay, 2).finished());
+    assert!(play(replay, 3).finished());
+}
+
+#[test]
+fn playback_without_a_length_never_finishes() {
+    assert!(!play("0 down Thrust\n1 up Thrust\n", 100).finished());
+}
+
+#[test]
+fn rules_are_read_from_the_header() {
+    let replay = Replay::parse("seed 1\nasteroid-collisions on\nmissile-edges wrap\n0 down Fire\n").unwrap();
+
+    assert_eq!(replay.rules, Some(Rules{
+        asteroid_collisions: true,
+        missile_edges: BoundaryBehavior::Wrap,
+        ..Rules::default()
+    }));
+}
+
+#[test]
+fn replay_without_rules_leaves_them_to_the_command_line() {
+    assert_eq!(Replay::parse("seed 1\n0 down Fire\n").unwrap().rules, None);
+}
+
+#[test]
+fn invalid_rule_is_rejected() {
+    assert!(Replay::parse("ship-edges sideways\n").is_err());
+    assert!(Replay::parse("asteroid-collisions maybe\n").is_err());
+}
+
+#[test]
+fn recorded_rules_load_back() {
+    let rules = Rules{
+        asteroid_collisions: true,
+        ship_edges: BoundaryBehavior::Clamp,
+        asteroid_edges: BoundaryBehavior::Wrap,
+        missile_edges: BoundaryBehavior::Bounce
+    };
+    let path = std::env::temp_dir().join(format!("asteroid-game-replay-{}.txt", std::process::id()));
+
+    let mut recorder = Recorder::new(7, rules);
+    recorder.record(&InputState::default());
+    recorder.save(&path).unwrap();
+    let replay = Replay::load(&path).unwrap();
+    fs::remove_file(&path).ok();
+
+    assert_eq!(replay.seed, Some(7));
+    assert_eq!(replay.rules, Some(rules));
+}
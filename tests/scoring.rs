@@ -0,0 +1,125 @@
+mod common;
+
+use specs::{World, WorldExt, Join};
+
+use asteroid_game::components;
+use asteroid_game::game;
+use asteroid_game::score::{self, SMALL_ASTEROID_POINTS};
+use asteroid_game::weapons::Weapon;
+
+use common::{still_asteroid, step, score};
+
+// A ship out of the way in the top left corner, and nothing else.
+fn ship_world() -> World {
+    common::ship_world(100.0, 100.0, 1, false)
+}
+
+fn combo_hits(ecs: &World) -> u32 {
+    ecs.read_storage::<components::Combo>().join().next().unwrap().hits
+}
+
+// Shoots a missile flying right from (x, 300).
+fn shoot(ecs: &mut World, x: f64) {
+    game::spawn_missile(ecs, components::Position{x, y: 300.0, rot: 90.0}, &Weapon{speed: 20.0, ..Weapon::default()});
+}
+
+// Shoots a small asteroid, destroying it within the tick.
+fn hit(ecs: &mut World) {
+    still_asteroid(ecs, 800.0, 300.0, 25);
+    shoot(ecs, 780.0);
+    step(ecs);
+}
+
+#[test]
+fn asteroids_score_by_size() {
+    assert_eq!(score::asteroid_points(128), score::LARGE_ASTEROID_POINTS);
+    assert_eq!(score::asteroid_points(64), score::MEDIUM_ASTEROID_POINTS);
+    assert_eq!(score::asteroid_points(32), SMALL_ASTEROID_POINTS);
+    assert_eq!(score::asteroid_points(25), SMALL_ASTEROID_POINTS);
+}
+
+#[test]
+fn combo_multiplier_grows_every_few_hits_up_to_the_maximum() {
+    assert_eq!(score::combo_multiplier(0), 1);
+    assert_eq!(score::combo_multiplier(score::HITS_PER_COMBO_STEP), 1);
+    assert_eq!(score::combo_multiplier(score::HITS_PER_COMBO_STEP + 1), 2);
+    assert_eq!(score::combo_multiplier(1000), score::MAX_COMBO);
+}
+
+#[test]
+fn hits_in_a_row_score_more() {
+    let mut ecs = ship_world();
+
+    for _ in 0..score::HITS_PER_COMBO_STEP + 1 {
+        hit(&mut ecs);
+    }
+
+    assert_eq!(combo_hits(&ecs), score::HITS_PER_COMBO_STEP + 1);
+    assert_eq!(score(&ecs), SMALL_ASTEROID_POINTS * (score::HITS_PER_COMBO_STEP + 2));
+}
+
+#[test]
+fn missed_shot_breaks_the_combo() {
+    let mut ecs = ship_world();
+    for _ in 0..score::HITS_PER_COMBO_STEP {
+        hit(&mut ecs);
+    }
+
+    // Leaves the arena without hitting anything.
+    shoot(&mut ecs, 1270.0);
+    step(&mut ecs);
+    assert_eq!(combo_hits(&ecs), 0);
+
+    hit(&mut ecs);
+    assert_eq!(score(&ecs), SMALL_ASTEROID_POINTS * (score::HITS_PER_COMBO_STEP + 1));
+}
+
+#[test]
+fn combo_runs_out_without_hits() {
+    let mut ecs = ship_world();
+    hit(&mut ecs);
+
+    for _ in 0..score::COMBO_WINDOW - 1 {
+        step(&mut ecs);
+    }
+    assert_eq!(combo_hits(&ecs), 1);
+    step(&mut ecs);
+    assert_eq!(combo_hits(&ecs), 0);
+}
+
+#[test]
+fn new_game_forgets_points_from_the_last_one() {
+    let mut ecs = ship_world();
+    ecs.write_resource::<score::Scoring>().kills.push((800.0, 300.0, SMALL_ASTEROID_POINTS));
+
+    game::new_game(&mut ecs);
+    step(&mut ecs);
+
+    assert_eq!(score(&ecs), 0);
+}
+
+#[test]
+fn destroyed_asteroid_leaves_a_rising_score_popup() {
+    let mut ecs = ship_world();
+    hit(&mut ecs);
+
+    {
+        let positions = ecs.read_storage::<components::Position>();
+        let popups = ecs.read_storage::<components::ScorePopup>();
+        let (pos, popup) = (&positions, &popups).join().next().unwrap();
+        assert_eq!((pos.x, pos.y), (800.0, 300.0));
+        assert_eq!(popup.points, SMALL_ASTEROID_POINTS);
+    }
+
+    step(&mut ecs);
+    {
+        let positions = ecs.read_storage::<components::Position>();
+        let popups = ecs.read_storage::<components::ScorePopup>();
+        assert!((&positions, &popups).join().next().unwrap().0.y < 300.0);
+    }
+
+    for _ in 1..score::POPUP_TICKS {
+        step(&mut ecs);
+    }
+    assert_eq!(ecs.read_storage::<components::ScorePopup>().join().count(), 0);
+}
@@ -0,0 +1,139 @@
+use specs::{World, WorldExt, Builder, Entity};
+
+use asteroid_game::resources::Arena;
+use asteroid_game::spatial::{GridEntry, SpatialGrid, CELL_SIZE};
+
+// A 5 by 3 cell arena, 640 by 384 pixels.
+fn grid() -> SpatialGrid {
+    let mut grid = SpatialGrid::default();
+    grid.clear(&Arena{width: 5 * CELL_SIZE as u32, height: 3 * CELL_SIZE as u32});
+    grid
+}
+
+fn add(grid: &mut SpatialGrid, ecs: &mut World, x: f64, y: f64, radius: f64, step: f64) -> Entity {
+    let entity = ecs.create_entity().build();
+    grid.insert(GridEntry{entity, x, y, radius, step});
+    entity
+}
+
+fn query(grid: &SpatialGrid, x: f64, y: f64, radius: f64) -> Vec<Entity> {
+    let mut found = Vec::new();
+    grid.query(x, y, radius, |entry| found.push(entry.entity));
+    found
+}
+
+fn query_path(grid: &SpatialGrid, start: (f64, f64), end: (f64, f64), radius: f64) -> Vec<Entity> {
+    let mut found = Vec::new();
+    grid.query_path(start, end, radius, |entry| found.push(entry.entity));
+    found
+}
+
+#[test]
+fn grid_that_was_never_sized_finds_nothing() {
+    let grid = SpatialGrid::default();
+    assert!(query(&grid, 10.0, 10.0, 1000.0).is_empty());
+    assert!(query_path(&grid, (0.0, 0.0), (100.0, 100.0), 1000.0).is_empty());
+}
+
+#[test]
+fn query_only_finds_overlapping_circles() {
+    let mut ecs = World::new();
+    let mut grid = grid();
+    let rock = add(&mut grid, &mut ecs, 300.0, 200.0, 10.0, 0.0);
+
+    assert!(query(&grid, 300.0, 225.0, 10.0).is_empty());
+    // Just touching isn't overlapping.
+    assert!(query(&grid, 300.0, 220.0, 10.0).is_empty());
+    assert_eq!(query(&grid, 300.0, 219.0, 10.0), vec![rock]);
+}
+
+#[test]
+fn entry_on_a_cell_border_is_found_from_both_sides() {
+    let mut ecs = World::new();
+    let mut grid = grid();
+    let rock = add(&mut grid, &mut ecs, CELL_SIZE, CELL_SIZE, 10.0, 0.0);
+
+    assert_eq!(query(&grid, CELL_SIZE - 8.0, CELL_SIZE, 5.0), vec![rock]);
+    assert_eq!(query(&grid, CELL_SIZE + 8.0, CELL_SIZE, 5.0), vec![rock]);
+    assert_eq!(query(&grid, CELL_SIZE - 8.0, CELL_SIZE - 8.0, 5.0), vec![rock]);
+    assert_eq!(query(&grid, CELL_SIZE, CELL_SIZE + 8.0, 5.0), vec![rock]);
+}
+
+#[test]
+fn entry_larger_than_a_cell_is_found_from_cells_away() {
+    let mut ecs = World::new();
+    let mut grid = grid();
+    let rock = add(&mut grid, &mut ecs, 500.0, 300.0, 300.0, 0.0);
+
+    // Two columns and a row away from the cell its center is in.
+    assert_eq!(query(&grid, 300.0, 150.0, 1.0), vec![rock]);
+    assert!(query(&grid, 250.0, 100.0, 1.0).is_empty());
+}
+
+#[test]
+fn query_larger_than_a_cell_finds_entries_cells_away() {
+    let mut ecs = World::new();
+    let mut grid = grid();
+    let near = add(&mut grid, &mut ecs, 600.0, 350.0, 5.0, 0.0);
+    add(&mut grid, &mut ecs, 630.0, 380.0, 5.0, 0.0);
+
+    assert_eq!(query(&grid, 100.0, 50.0, 600.0), vec![near]);
+}
+
+#[test]
+fn query_at_the_edges_finds_entries_outside_the_arena() {
+    let mut ecs = World::new();
+    let mut grid = grid();
+    // Asteroids wrapping around stick out of the arena for a moment.
+    let left = add(&mut grid, &mut ecs, -10.0, 20.0, 16.0, 0.0);
+    let bottom_right = add(&mut grid, &mut ecs, 650.0, 390.0, 16.0, 0.0);
+
+    assert_eq!(query(&grid, 5.0, 20.0, 5.0), vec![left]);
+    assert_eq!(query(&grid, 635.0, 380.0, 5.0), vec![bottom_right]);
+    assert_eq!(query(&grid, -20.0, 20.0, 5.0), vec![left]);
+    // Far away from the arena a query still works, it just finds nothing.
+    assert!(query(&grid, -1000.0, -1000.0, 10.0).is_empty());
+    assert!(query(&grid, 5000.0, 5000.0, 10.0).is_empty());
+}
+
+#[test]
+fn query_path_finds_entries_along_the_whole_path() {
+    let mut ecs = World::new();
+    let mut grid = grid();
+    let first = add(&mut grid, &mut ecs, 100.0, 64.0, 10.0, 0.0);
+    let second = add(&mut grid, &mut ecs, 500.0, 64.0, 10.0, 0.0);
+    add(&mut grid, &mut ecs, 300.0, 320.0, 10.0, 0.0);
+
+    let mut found = query_path(&grid, (64.0, 64.0), (576.0, 64.0), 2.0);
+    found.sort();
+    assert_eq!(found, vec![first, second]);
+
+    // The same going the other way.
+    let mut found = query_path(&grid, (576.0, 64.0), (64.0, 64.0), 2.0);
+    found.sort();
+    assert_eq!(found, vec![first, second]);
+}
+
+#[test]
+fn query_path_reaches_as_far_as_entries_have_moved() {
+    let mut ecs = World::new();
+    let mut grid = grid();
+    let rock = add(&mut grid, &mut ecs, 100.0, 200.0, 10.0, 0.0);
+    assert!(query_path(&grid, (40.0, 40.0), (80.0, 40.0), 2.0).is_empty());
+
+    // A fast asteroid anywhere means any other one could have come that far.
+    let fast = add(&mut grid, &mut ecs, 600.0, 350.0, 10.0, 150.0);
+    let found = query_path(&grid, (40.0, 40.0), (80.0, 40.0), 2.0);
+    assert!(found.contains(&rock));
+    assert!(!found.contains(&fast));
+}
+
+#[test]
+fn query_path_across_the_edge_finds_entries_outside_the_arena() {
+    let mut ecs = World::new();
+    let mut grid = grid();
+    let outside = add(&mut grid, &mut ecs, 660.0, 40.0, 10.0, 0.0);
+
+    assert_eq!(query_path(&grid, (630.0, 40.0), (650.0, 40.0), 2.0), vec![outside]);
+    assert!(query_path(&grid, (-50.0, 300.0), (-10.0, 300.0), 2.0).is_empty());
+}
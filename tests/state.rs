@@ -0,0 +1,85 @@
+use asteroid_game::state::{GameState, StateMachine};
+
+// A state machine on the given screen, reached the way the game gets there.
+fn machine_at(path: &[GameState]) -> StateMachine {
+    let mut state = StateMachine::new();
+    for to in path {
+        assert!(state.transition(*to), "can't go from {:?} to {:?}", state.current(), to);
+    }
+    state
+}
+
+#[test]
+fn high_score_goes_through_name_entry_back_to_the_title() {
+    let mut state = machine_at(&[GameState::Playing, GameState::GameOver, GameState::EnterName]);
+    assert!(!state.runs_simulation());
+
+    assert!(state.transition(GameState::Title));
+    assert_eq!(state.current(), GameState::Title);
+}
+
+#[test]
+fn game_starts_on_the_title_screen() {
+    let state = StateMachine::new();
+    assert_eq!(state.current(), GameState::Title);
+    assert!(!state.runs_simulation());
+}
+
+#[test]
+fn escape_while_playing_pauses_instead_of_quitting() {
+    // The front end turns Escape into this transition while playing.
+    let mut state = machine_at(&[GameState::Playing]);
+    assert!(state.runs_simulation());
+
+    assert!(state.transition(GameState::Paused));
+    assert_eq!(state.current(), GameState::Paused);
+    assert!(!state.runs_simulation());
+
+    assert!(state.transition(GameState::Playing));
+    assert!(state.runs_simulation());
+}
+
+#[test]
+fn paused_game_can_be_quit_to_the_title() {
+    let mut state = machine_at(&[GameState::Playing, GameState::Paused]);
+    assert!(state.transition(GameState::Title));
+}
+
+#[test]
+fn transitions_that_make_no_sense_are_refused() {
+    let refused = [
+        (vec![], GameState::Paused),
+        (vec![], GameState::GameOver),
+        (vec![], GameState::EnterName),
+        (vec![GameState::Playing], GameState::EnterName),
+        (vec![GameState::Playing, GameState::GameOver], GameState::Playing),
+        (vec![GameState::Playing, GameState::GameOver], GameState::Paused),
+        (vec![GameState::Playing, GameState::GameOver, GameState::EnterName], GameState::Playing)
+    ];
+
+    for (path, to) in refused {
+        let mut state = machine_at(&path);
+        let from = state.current();
+        assert!(!state.transition(to), "{:?} to {:?} was allowed", from, to);
+        assert_eq!(state.current(), from);
+    }
+}
+
+#[test]
+fn settings_go_back_where_they_were_opened() {
+    let mut state = machine_at(&[GameState::Settings]);
+    assert!(!state.transition(GameState::Playing));
+    assert!(state.close_settings());
+    assert_eq!(state.current(), GameState::Title);
+
+    let mut state = machine_at(&[GameState::Playing, GameState::Paused, GameState::Settings]);
+    assert!(!state.transition(GameState::Title));
+    assert!(state.close_settings());
+    assert_eq!(state.current(), GameState::Paused);
+}
+
+#[test]
+fn settings_cannot_be_opened_while_playing() {
+    let mut state = machine_at(&[GameState::Playing]);
+    assert!(!state.transition(GameState::Settings));
+}
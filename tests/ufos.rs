@@ -0,0 +1,114 @@
+mod common;
+
+use specs::{World, WorldExt, Join};
+
+use asteroid_game::components::{self, UfoSize};
+use asteroid_game::game;
+use asteroid_game::score::SMALL_ASTEROID_POINTS;
+use asteroid_game::ufo;
+use asteroid_game::weapons::Weapon;
+
+use common::{empty_world, still_asteroid, step, gamedata};
+
+fn ufo_world(ufo_timer: u32) -> World {
+    let ecs = empty_world(1, false);
+    for gamedata in (&mut ecs.write_storage::<components::GameData>()).join() {
+        gamedata.ufo_timer = ufo_timer;
+    }
+    ecs
+}
+
+#[test]
+fn ufo_comes_in_on_an_edge_once_the_timer_runs_out() {
+    let mut ecs = ufo_world(2);
+
+    ufo::update_ufos(&mut ecs, Some((640.0, 320.0)));
+    assert_eq!(ecs.read_storage::<components::Ufo>().join().count(), 0);
+    ufo::update_ufos(&mut ecs, Some((640.0, 320.0)));
+
+    let positions = ecs.read_storage::<components::Position>();
+    let ufos = ecs.read_storage::<components::Ufo>();
+    let (pos, _) = (&positions, &ufos).join().next().unwrap();
+    assert!(pos.x == 0.0 || pos.x == 1280.0);
+    assert_eq!(gamedata(&ecs, |gamedata| gamedata.ufo_timer), 1140);
+}
+
+#[test]
+fn ufo_timer_waits_for_the_ship() {
+    let mut ecs = ufo_world(1);
+
+    ufo::update_ufos(&mut ecs, None);
+
+    assert_eq!(ecs.read_storage::<components::Ufo>().join().count(), 0);
+}
+
+#[test]
+fn small_ufo_aims_at_the_ship() {
+    let mut ecs = ufo_world(ufo::UFO_INTERVAL);
+    let saucer = ufo::spawn_ufo(&mut ecs, components::Position{x: 300.0, y: 300.0, rot: 90.0}, UfoSize::Small);
+    ecs.write_storage::<components::Ufo>().get_mut(saucer).unwrap().fire_timer = 1;
+
+    // Straight below it.
+    ufo::update_ufos(&mut ecs, Some((300.0, 500.0)));
+
+    let positions = ecs.read_storage::<components::Position>();
+    let missiles = ecs.read_storage::<components::Missile>();
+    let (pos, missile) = (&positions, &missiles).join().next().unwrap();
+    assert!(missile.hostile);
+    assert!((pos.rot - 180.0).abs() <= 11.0, "aimed at {}", pos.rot);
+}
+
+#[test]
+fn player_missile_shoots_down_a_ufo_for_bonus_score() {
+    let mut ecs = ufo_world(ufo::UFO_INTERVAL);
+    let saucer = ufo::spawn_ufo(&mut ecs, components::Position{x: 400.0, y: 300.0, rot: 90.0}, UfoSize::Large);
+    let missile = game::spawn_missile(&mut ecs, components::Position{x: 340.0, y: 300.0, rot: 90.0}, &Weapon{speed: 40.0, ..Weapon::default()});
+
+    step(&mut ecs);
+
+    assert!(!ecs.is_alive(saucer));
+    assert!(!ecs.is_alive(missile));
+    assert_eq!(gamedata(&ecs, |gamedata| gamedata.score), UfoSize::Large.score());
+}
+
+#[test]
+fn missile_that_hit_an_asteroid_cannot_also_hit_a_ufo() {
+    let mut ecs = ufo_world(ufo::UFO_INTERVAL);
+    let saucer = ufo::spawn_ufo(&mut ecs, components::Position{x: 400.0, y: 300.0, rot: 90.0}, UfoSize::Large);
+    let asteroid = still_asteroid(&mut ecs, 360.0, 300.0, 25);
+    // Fast enough to reach both within the tick, the asteroid comes first.
+    game::spawn_missile(&mut ecs, components::Position{x: 330.0, y: 300.0, rot: 90.0}, &Weapon{speed: 80.0, ..Weapon::default()});
+
+    step(&mut ecs);
+
+    assert!(!ecs.is_alive(asteroid));
+    assert!(ecs.is_alive(saucer));
+    assert_eq!(gamedata(&ecs, |gamedata| gamedata.score), SMALL_ASTEROID_POINTS);
+}
+
+#[test]
+fn ufo_shot_breaks_asteroids_without_scoring() {
+    let mut ecs = ufo_world(ufo::UFO_INTERVAL);
+    let asteroid = still_asteroid(&mut ecs, 400.0, 300.0, 25);
+    let shot = game::spawn_missile(&mut ecs, components::Position{x: 380.0, y: 300.0, rot: 90.0}, &ufo::ufo_gun());
+    ecs.write_storage::<components::Missile>().get_mut(shot).unwrap().hostile = true;
+
+    step(&mut ecs);
+
+    assert!(!ecs.is_alive(asteroid));
+    assert_eq!(gamedata(&ecs, |gamedata| gamedata.score), 0);
+}
+
+#[test]
+fn ufo_shot_destroys_the_ship() {
+    let mut ecs = ufo_world(ufo::UFO_INTERVAL);
+    game::spawn_player(&mut ecs, components::Position{x: 400.0, y: 300.0, rot: 0.0}, 0);
+    let shot = game::spawn_missile(&mut ecs, components::Position{x: 380.0, y: 300.0, rot: 90.0}, &ufo::ufo_gun());
+    ecs.write_storage::<components::Missile>().get_mut(shot).unwrap().hostile = true;
+
+    step(&mut ecs);
+
+    assert_eq!(ecs.read_storage::<components::Player>().join().count(), 0);
+    assert!(!ecs.is_alive(shot));
+    assert_eq!(gamedata(&ecs, |gamedata| gamedata.lives), 2);
+}
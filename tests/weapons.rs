@@ -0,0 +1,141 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use specs::{World, WorldExt, Join};
+use vector2d::Vector2D;
+
+use asteroid_game::components;
+use asteroid_game::game;
+use asteroid_game::input::{Action, InputState};
+use asteroid_game::resources::Arena;
+use asteroid_game::weapons::{Weapon, Weapons};
+
+// Missile flying right through an empty arena.
+fn missile_world(weapon: Weapon) -> (World, specs::Entity) {
+    let mut ecs = game::create_world(Arena::default(), 1);
+    let missile = game::spawn_missile(&mut ecs, components::Position{x: 100.0, y: 300.0, rot: 90.0}, &weapon);
+    (ecs, missile)
+}
+
+fn run_systems(ecs: &mut World, ticks: u32) {
+    for _ in 0..ticks {
+        common::step(ecs);
+    }
+}
+
+#[test]
+fn missile_is_deleted_once_it_has_flown_its_range() {
+    let (mut ecs, missile) = missile_world(Weapon{speed: 10.0, lifetime: None, range: Some(50.0), ..Weapon::default()});
+
+    run_systems(&mut ecs, 4);
+    assert!(ecs.is_alive(missile));
+    run_systems(&mut ecs, 1);
+    assert!(!ecs.is_alive(missile));
+}
+
+#[test]
+fn missile_is_deleted_once_its_lifetime_is_over() {
+    let (mut ecs, missile) = missile_world(Weapon{speed: 1.0, lifetime: Some(30), range: None, ..Weapon::default()});
+
+    run_systems(&mut ecs, 29);
+    assert!(ecs.is_alive(missile));
+    run_systems(&mut ecs, 1);
+    assert!(!ecs.is_alive(missile));
+}
+
+#[test]
+fn holding_fire_shoots_once_per_cooldown() {
+    let mut ecs = game::create_world(Arena::default(), 1);
+    ecs.insert(Weapons{list: vec![Weapon{cooldown: 10, max_active: None, ..Weapon::default()}]});
+    game::load_world(&mut ecs);
+    let mut dispatcher = game::build_dispatcher();
+    let mut input = InputState::default();
+
+    input.press(Action::Fire);
+    for _ in 0..21 {
+        game::step(&mut ecs, &mut dispatcher, &mut input);
+    }
+
+    assert_eq!(ecs.read_storage::<components::Missile>().join().count(), 3);
+}
+
+#[test]
+fn spread_shot_fans_out_its_shots() {
+    let mut ecs = game::create_world(Arena::default(), 1);
+    ecs.insert(Weapons{list: vec![Weapon{shots: 3, spread: 40.0, ..Weapon::default()}]});
+    game::load_world(&mut ecs);
+    let mut dispatcher = game::build_dispatcher();
+    let mut input = InputState::default();
+
+    input.press(Action::Fire);
+    game::step(&mut ecs, &mut dispatcher, &mut input);
+
+    let positions = ecs.read_storage::<components::Position>();
+    let missiles = ecs.read_storage::<components::Missile>();
+    let mut headings: Vec<f64> = (&positions, &missiles).join().map(|(pos, _)| pos.rot).collect();
+    headings.sort_by(f64::total_cmp);
+    assert_eq!(headings, [-20.0, 0.0, 20.0]);
+}
+
+#[test]
+fn switch_weapon_goes_to_the_next_weapon() {
+    let mut ecs = game::create_world(Arena::default(), 1);
+    ecs.insert(Weapons{list: vec![Weapon::default(), Weapon{name: "laser".to_string(), ..Weapon::default()}]});
+    game::load_world(&mut ecs);
+    let mut dispatcher = game::build_dispatcher();
+    let mut input = InputState::default();
+
+    input.press(Action::SwitchWeapon);
+    game::step(&mut ecs, &mut dispatcher, &mut input);
+
+    assert_eq!(ecs.read_storage::<components::Player>().join().next().unwrap().weapon, 1);
+}
+
+#[test]
+fn homing_missile_turns_towards_the_nearest_asteroid() {
+    let (mut ecs, missile) = missile_world(Weapon{turn_rate: Some(10.0), range: None, ..Weapon::default()});
+    // Straight above the missile, which starts out flying right.
+    let asteroid = game::create_asteroid(&mut ecs, components::Position{x: 100.0, y: 100.0, rot: 0.0}, 64);
+    ecs.write_storage::<components::Velocity>().insert(asteroid, components::Velocity(Vector2D::new(0.0, 0.0))).unwrap();
+
+    run_systems(&mut ecs, 20);
+
+    let positions = ecs.read_storage::<components::Position>();
+    let velocities = ecs.read_storage::<components::Velocity>();
+    let pos = positions.get(missile).unwrap();
+    // Heading straight for it by now, without having slowed down.
+    let wanted = (100.0 - pos.x).atan2(pos.y - 100.0).to_degrees().rem_euclid(360.0);
+    assert!((pos.rot - wanted).abs() < 1e-6, "heading {} instead of {}", pos.rot, wanted);
+    assert!((velocities.get(missile).unwrap().0.length() - 5.0).abs() < 1e-9);
+}
+
+#[test]
+fn piercing_shot_destroys_every_asteroid_on_its_path() {
+    let (mut ecs, missile) = missile_world(Weapon{speed: 100.0, piercing: true, ..Weapon::default()});
+    let mut asteroids = Vec::new();
+    for x in [150.0, 180.0] {
+        let asteroid = game::create_asteroid(&mut ecs, components::Position{x, y: 300.0, rot: 0.0}, 25);
+        ecs.write_storage::<components::Velocity>().insert(asteroid, components::Velocity(Vector2D::new(0.0, 0.0))).unwrap();
+        asteroids.push(asteroid);
+    }
+
+    run_systems(&mut ecs, 1);
+
+    assert!(asteroids.iter().all(|asteroid| !ecs.is_alive(*asteroid)));
+    assert!(ecs.is_alive(missile));
+}
+
+#[test]
+fn weapons_file_in_the_repository_is_valid() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("data/weapons.toml");
+    let weapons = Weapons::parse(&fs::read_to_string(path).unwrap()).unwrap();
+    let names: Vec<&str> = weapons.list.iter().map(|weapon| weapon.name.as_str()).collect();
+    assert_eq!(names, ["bullet", "spread", "homing", "laser"]);
+}
+
+#[test]
+fn weapons_without_any_weapon_are_refused() {
+    assert!(Weapons::parse("weapon = []").is_err());
+}